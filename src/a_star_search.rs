@@ -1,11 +1,26 @@
 use std::{collections::BinaryHeap, hash::Hash};
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
+
+use crate::cost::Cost;
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<N, C> {
+    pub path: Vec<N>,
+    pub cost: C,
+    // Nodes popped from the open set and expanded, stale queue entries are not counted
+    pub expanded: usize,
+    // Entries pushed onto the open set, including the start node
+    pub pushed: usize,
+    // Every expanded node, only populated when requested
+    pub closed: Option<HashSet<N>>,
+}
 
 pub trait AStarSearch {
     type Node: Clone + Eq + Hash;
+    type Cost: Cost;
 
-    fn weighted_neighbours(&self, node: &Self::Node) -> Option<Vec<(Self::Node, f64)>>;
+    fn weighted_neighbours(&self, node: &Self::Node) -> Option<Vec<(Self::Node, Self::Cost)>>;
 
     fn reconstruct_path(
         &self,
@@ -28,22 +43,73 @@ pub trait AStarSearch {
         &self,
         start: Self::Node,
         goal: Self::Node,
-        heuristic: fn(Self::Node, Self::Node) -> f64,
+        heuristic: fn(Self::Node, Self::Node) -> Self::Cost,
     ) -> Option<Vec<Self::Node>> {
-        struct Elem<K>(K, f64);
-        impl<K> PartialEq for Elem<K> {
+        self.a_star_search_result(start, goal, heuristic)
+            .map(|result| result.path)
+    }
+
+    fn a_star_search_result(
+        &self,
+        start: Self::Node,
+        goal: Self::Node,
+        heuristic: fn(Self::Node, Self::Node) -> Self::Cost,
+    ) -> Option<SearchResult<Self::Node, Self::Cost>> {
+        self.a_star_search_where(
+            start,
+            |node| *node == goal,
+            |node| heuristic(node.clone(), goal.clone()),
+            false,
+        )
+    }
+
+    // Stops at whichever goal is reached first, the heuristic is the minimum over all goals
+    fn a_star_search_any(
+        &self,
+        start: Self::Node,
+        goals: &[Self::Node],
+        heuristic: fn(Self::Node, Self::Node) -> Self::Cost,
+    ) -> Option<SearchResult<Self::Node, Self::Cost>> {
+        self.a_star_search_where(
+            start,
+            |node| goals.contains(node),
+            |node| {
+                goals
+                    .iter()
+                    .map(|goal| heuristic(node.clone(), goal.clone()))
+                    .min_by(|a, b| a.cmp_cost(b))
+                    .unwrap_or(Self::Cost::ZERO)
+            },
+            false,
+        )
+    }
+
+    fn a_star_search_where<P, H>(
+        &self,
+        start: Self::Node,
+        is_goal: P,
+        heuristic: H,
+        record_closed: bool,
+    ) -> Option<SearchResult<Self::Node, Self::Cost>>
+    where
+        P: Fn(&Self::Node) -> bool,
+        H: Fn(&Self::Node) -> Self::Cost,
+    {
+        // Ordered by f score, the g score is kept to detect stale entries
+        struct Elem<K, C>(K, C, C);
+        impl<K, C: Cost> PartialEq for Elem<K, C> {
             fn eq(&self, other: &Self) -> bool {
                 self.1 == other.1
             }
         }
-        impl<K> Eq for Elem<K> {}
-        impl<K> Ord for Elem<K> {
+        impl<K, C: Cost> Eq for Elem<K, C> {}
+        impl<K, C: Cost> Ord for Elem<K, C> {
             fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-                self.1.partial_cmp(&other.1).unwrap().reverse()
+                self.1.cmp_cost(&other.1).reverse()
             }
         }
 
-        impl<K> PartialOrd for Elem<K> {
+        impl<K, C: Cost> PartialOrd for Elem<K, C> {
             fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
                 Some(self.cmp(other))
             }
@@ -51,34 +117,357 @@ pub trait AStarSearch {
 
         let mut came_from = HashMap::new();
         let mut g_score = HashMap::new();
-        let mut f_score = HashMap::new();
-        g_score.insert(start.clone(), 0.0);
-        f_score.insert(start.clone(), heuristic(start.clone(), goal.clone()));
+        g_score.insert(start.clone(), Self::Cost::ZERO);
+
+        let mut closed = HashSet::new();
+        let mut expanded = 0;
+        let mut pushed = 1;
+
+        let mut open_set: BinaryHeap<Elem<Self::Node, Self::Cost>> = BinaryHeap::new();
+        open_set.push(Elem(start.clone(), heuristic(&start), Self::Cost::ZERO));
 
-        let mut open_set: BinaryHeap<Elem<Self::Node>> = BinaryHeap::new();
-        open_set.push(Elem(start.clone(), f_score[&start]));
+        while let Some(Elem(v, _, g)) = open_set.pop() {
+            if g > g_score[&v] {
+                continue;
+            }
+
+            if is_goal(&v) {
+                return Some(SearchResult {
+                    path: self.reconstruct_path(came_from, v),
+                    cost: g,
+                    expanded,
+                    pushed,
+                    closed: record_closed.then_some(closed),
+                });
+            }
 
-        while let Some(Elem(v, _)) = open_set.pop() {
-            if v == goal {
-                return Some(self.reconstruct_path(came_from, goal));
+            expanded += 1;
+            if record_closed {
+                closed.insert(v.clone());
             }
 
             if let Some(weighted_neighbours) = self.weighted_neighbours(&v) {
                 for (u, w) in weighted_neighbours {
-                    let tenative_g_score = g_score[&v] + w;
-                    if tenative_g_score < *g_score.get(&u).unwrap_or(&f64::INFINITY) {
+                    let tenative_g_score = g + w;
+                    if tenative_g_score < *g_score.get(&u).unwrap_or(&Self::Cost::INFINITY) {
+                        came_from.insert(u.clone(), v.clone());
+                        g_score.insert(u.clone(), tenative_g_score);
+                        let f_score = tenative_g_score + heuristic(&u);
+                        open_set.push(Elem(u, f_score, tenative_g_score));
+                        pushed += 1;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    // Iterative deepening A*, a depth first search bounded by an f score that grows to the
+    // smallest f score that went over it. Only the current path is kept in memory, unless
+    // `transposition_table` is set, then the best g score of every node reached in an
+    // iteration is kept so nodes reached again through a worse path are skipped
+    fn ida_star_search<P, H>(
+        &self,
+        start: Self::Node,
+        is_goal: P,
+        heuristic: H,
+        transposition_table: bool,
+    ) -> Option<SearchResult<Self::Node, Self::Cost>>
+    where
+        P: Fn(&Self::Node) -> bool,
+        H: Fn(&Self::Node) -> Self::Cost,
+    {
+        let mut expanded = 0;
+        let mut pushed = 1;
+
+        if is_goal(&start) {
+            return Some(SearchResult {
+                path: vec![start],
+                cost: Self::Cost::ZERO,
+                expanded,
+                pushed,
+                closed: None,
+            });
+        }
+
+        let mut threshold = heuristic(&start);
+
+        loop {
+            let mut next_threshold = Self::Cost::INFINITY;
+            let mut table = HashMap::new();
+
+            // The nodes on the current path with their g scores and the neighbours left to try
+            let neighbours = self.weighted_neighbours(&start).unwrap_or_default();
+            let mut stack = vec![(start.clone(), Self::Cost::ZERO, neighbours.into_iter())];
+            expanded += 1;
+
+            while let Some((_, g, neighbours)) = stack.last_mut() {
+                let Some((u, w)) = neighbours.next() else {
+                    stack.pop();
+                    continue;
+                };
+
+                let g = *g + w;
+                if stack.iter().any(|(v, _, _)| *v == u) {
+                    continue;
+                }
+
+                if transposition_table {
+                    if g >= *table.get(&u).unwrap_or(&Self::Cost::INFINITY) {
+                        continue;
+                    }
+                    table.insert(u.clone(), g);
+                }
+
+                pushed += 1;
+                let f_score = g + heuristic(&u);
+                if f_score > threshold {
+                    if f_score < next_threshold {
+                        next_threshold = f_score;
+                    }
+                    continue;
+                }
+
+                if is_goal(&u) {
+                    let mut path = stack.into_iter().map(|(v, _, _)| v).collect::<Vec<_>>();
+                    path.push(u);
+
+                    return Some(SearchResult {
+                        path,
+                        cost: g,
+                        expanded,
+                        pushed,
+                        closed: None,
+                    });
+                }
+
+                expanded += 1;
+                let neighbours = self.weighted_neighbours(&u).unwrap_or_default();
+                stack.push((u, g, neighbours.into_iter()));
+            }
+
+            if next_threshold == Self::Cost::INFINITY {
+                return None;
+            }
+            threshold = next_threshold;
+        }
+    }
+
+    // Breadth first, keeping only the `width` nodes with the best f scores of every layer.
+    // Memory is bounded by the width times the depth, but the search is neither complete nor
+    // optimal once nodes fall outside the beam
+    fn beam_search<P, H>(
+        &self,
+        start: Self::Node,
+        is_goal: P,
+        heuristic: H,
+        width: usize,
+    ) -> Option<SearchResult<Self::Node, Self::Cost>>
+    where
+        P: Fn(&Self::Node) -> bool,
+        H: Fn(&Self::Node) -> Self::Cost,
+    {
+        let mut came_from = HashMap::new();
+        let mut g_score = HashMap::new();
+        g_score.insert(start.clone(), Self::Cost::ZERO);
+
+        let mut expanded = 0;
+        let mut pushed = 1;
+        let mut beam = vec![start];
+
+        while !beam.is_empty() {
+            if let Some(goal) = beam
+                .iter()
+                .filter(|v| is_goal(v))
+                .min_by(|a, b| g_score[*a].cmp_cost(&g_score[*b]))
+            {
+                return Some(SearchResult {
+                    cost: g_score[goal],
+                    path: self.reconstruct_path(came_from, goal.clone()),
+                    expanded,
+                    pushed,
+                    closed: None,
+                });
+            }
+
+            let mut candidates = HashSet::new();
+            for v in beam {
+                expanded += 1;
+                let g = g_score[&v];
+
+                for (u, w) in self.weighted_neighbours(&v).unwrap_or_default() {
+                    let tenative_g_score = g + w;
+                    if tenative_g_score < *g_score.get(&u).unwrap_or(&Self::Cost::INFINITY) {
                         came_from.insert(u.clone(), v.clone());
                         g_score.insert(u.clone(), tenative_g_score);
-                        f_score.insert(
-                            u.clone(),
-                            tenative_g_score + heuristic(u.clone(), goal.clone()),
-                        );
-                        open_set.push(Elem(u.clone(), f_score[&u]));
+                        candidates.insert(u);
                     }
                 }
             }
+
+            pushed += candidates.len();
+
+            let mut candidates = candidates
+                .into_iter()
+                .map(|u| (g_score[&u] + heuristic(&u), u))
+                .collect::<Vec<_>>();
+            candidates.sort_by(|a, b| a.0.cmp_cost(&b.0));
+            candidates.truncate(width);
+
+            beam = candidates.into_iter().map(|(_, u)| u).collect();
         }
 
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    fn no_heuristic(_: char, _: char) -> u32 {
+        0
+    }
+
+    // a -> b -> d is cheaper than a -> c -> d, and e is only reachable through d
+    fn graph() -> Graph<char, u32> {
+        let mut graph = Graph::new();
+        for (u, v, w) in [
+            ('a', 'b', 1),
+            ('b', 'd', 1),
+            ('a', 'c', 1),
+            ('c', 'd', 5),
+            ('d', 'e', 2),
+        ] {
+            graph.add_edge(u, v, w);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_a_star_search_result() {
+        let result = graph()
+            .a_star_search_result('a', 'e', no_heuristic)
+            .unwrap();
+
+        assert_eq!(result.path, vec!['a', 'b', 'd', 'e']);
+        assert_eq!(result.cost, 4);
+        assert_eq!(result.expanded, 4);
+        assert_eq!(result.pushed, 5);
+        assert!(result.closed.is_none());
+    }
+
+    #[test]
+    fn test_a_star_search_any() {
+        let result = graph()
+            .a_star_search_any('a', &['e', 'c'], no_heuristic)
+            .unwrap();
+
+        assert_eq!(result.path, vec!['a', 'c']);
+        assert_eq!(result.cost, 1);
+    }
+
+    #[test]
+    fn test_a_star_search_where_records_closed_set() {
+        let result = graph()
+            .a_star_search_where('a', |v| *v == 'd', |_| 0, true)
+            .unwrap();
+
+        assert_eq!(result.cost, 2);
+        assert_eq!(result.closed, Some(HashSet::from(['a', 'b', 'c'])));
+    }
+
+    #[test]
+    fn test_a_star_search_unreachable() {
+        assert!(graph().a_star_search('e', 'a', no_heuristic).is_none());
+    }
+
+    #[test]
+    fn test_ida_star_search() {
+        for transposition_table in [false, true] {
+            let result = graph()
+                .ida_star_search('a', |v| *v == 'e', |_| 0, transposition_table)
+                .unwrap();
+
+            assert_eq!(result.path, vec!['a', 'b', 'd', 'e']);
+            assert_eq!(result.cost, 4);
+        }
+
+        assert!(graph()
+            .ida_star_search('e', |v| *v == 'a', |_| 0, false)
+            .is_none());
+    }
+
+    // An open 12x12 grid with unit costs, every cell has many shortest paths through it
+    fn open_grid() -> Graph<(i32, i32), u32> {
+        let mut graph = Graph::new();
+        for i in 0..12 {
+            for j in 0..12 {
+                for (di, dj) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                    let (ni, nj) = (i + di, j + dj);
+                    if (0..12).contains(&ni) && (0..12).contains(&nj) {
+                        graph.add_edge((i, j), (ni, nj), 1);
+                    }
+                }
+            }
+        }
+        graph.remove_vertex((5, 5));
+        graph
+    }
+
+    fn manhattan(a: (i32, i32), b: (i32, i32)) -> u32 {
+        a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+    }
+
+    #[test]
+    fn test_ida_star_search_transposition_table() {
+        let grid = open_grid();
+        let goal = (11, 8);
+        let heuristic = |v: &(i32, i32)| manhattan(*v, goal) / 2;
+
+        let plain = grid
+            .ida_star_search((0, 0), |v| *v == goal, heuristic, false)
+            .unwrap();
+        let table = grid
+            .ida_star_search((0, 0), |v| *v == goal, heuristic, true)
+            .unwrap();
+        let a_star = grid.a_star_search_result((0, 0), goal, manhattan).unwrap();
+
+        assert_eq!(plain.cost, a_star.cost);
+        assert_eq!(table.cost, a_star.cost);
+        assert!(table.expanded < plain.expanded);
+    }
+
+    #[test]
+    fn test_beam_search() {
+        let grid = open_grid();
+        let goal = (11, 8);
+        let heuristic = |v: &(i32, i32)| manhattan(*v, goal);
+
+        let wide = grid
+            .beam_search((0, 0), |v| *v == goal, heuristic, 1000)
+            .unwrap();
+        let narrow = grid
+            .beam_search((0, 0), |v| *v == goal, heuristic, 2)
+            .unwrap();
+
+        assert_eq!(wide.cost, 19);
+        assert_eq!(narrow.cost, 19);
+        assert!(narrow.expanded < wide.expanded);
+
+        // A misleading heuristic makes a beam of one drop b and pay for c -> d
+        let misleading = |v: &char| if *v == 'b' { 10 } else { 0 };
+        let result = graph()
+            .beam_search('a', |v| *v == 'd', misleading, 1)
+            .unwrap();
+        assert_eq!(result.path, vec!['a', 'c', 'd']);
+        assert_eq!(result.cost, 6);
+
+        let result = graph()
+            .beam_search('a', |v| *v == 'd', misleading, 2)
+            .unwrap();
+        assert_eq!(result.cost, 2);
+    }
+}
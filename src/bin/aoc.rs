@@ -0,0 +1,27 @@
+use std::io::{stdin, stdout};
+use std::process::exit;
+
+use aoc_2024::file_utils::read_to_string;
+use aoc_2024::q17::{debugger_from_input, run_repl};
+
+const USAGE: &str = "usage: aoc debug q17 [input file]";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["debug", "q17", rest @ ..] if rest.len() <= 1 => {
+            let path = rest.first().copied().unwrap_or("data/q17.txt");
+            let input = read_to_string(path).expect("Failed to read input");
+
+            let mut debugger = debugger_from_input(&input);
+            run_repl(&mut debugger, stdin().lock(), stdout().lock())
+                .expect("Failed to run the debugger");
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(1);
+        }
+    }
+}
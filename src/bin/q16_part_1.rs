@@ -15,6 +15,6 @@ mod tests {
     fn test_get_shortest_path_cost() {
         let input = read_to_string("data/q16.txt").expect("Failed to read input");
         let result = get_shortest_path_cost(&input);
-        assert_eq!(result, 66404.0);
+        assert_eq!(result, 66404);
     }
 }
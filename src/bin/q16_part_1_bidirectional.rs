@@ -0,0 +1,40 @@
+use aoc_2024::file_utils::read_to_string;
+use aoc_2024::q16::{compare_shortest_path_searches, get_shortest_path_cost_bidirectional};
+
+fn main() {
+    let input = read_to_string("data/q16.txt").expect("Failed to read input");
+    let result = get_shortest_path_cost_bidirectional(&input);
+    println!("{}", result);
+
+    let (two_sided, one_sided) = compare_shortest_path_searches(&input).expect("No path found");
+    println!(
+        "Expanded {} vertices, {} with a one sided search",
+        two_sided.expanded, one_sided.expanded
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_shortest_path_cost_bidirectional() {
+        let input = read_to_string("data/q16.txt").expect("Failed to read input");
+        let result = get_shortest_path_cost_bidirectional(&input);
+        assert_eq!(result, 66404);
+    }
+
+    #[test]
+    fn test_expands_fewer_vertices_than_dijkstra() {
+        let input = read_to_string("data/q16.txt").expect("Failed to read input");
+        let (two_sided, one_sided) = compare_shortest_path_searches(&input).unwrap();
+
+        assert_eq!((two_sided.cost, one_sided.cost), (66404, 66404));
+        assert!(
+            two_sided.expanded < one_sided.expanded,
+            "expanded {} vertices, {} with Dijkstra",
+            two_sided.expanded,
+            one_sided.expanded
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use aoc_2024::file_utils::read_to_string;
+use aoc_2024::q17::decompile_input;
+
+fn main() {
+    let input = read_to_string("data/q17.txt").expect("Failed to read input");
+    match decompile_input(&input) {
+        Ok(decompiled) => print!("{}", decompiled),
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompile_input() {
+        let input = read_to_string("data/q17.txt").expect("Failed to read input");
+        let decompiled = decompile_input(&input).unwrap();
+        let expected = "\
+do {
+    out(((a & 7) ^ (a >> ((a & 7) ^ 1)) ^ 4) & 7)
+    a = a >> 3
+} while a != 0
+";
+        assert_eq!(decompiled.to_string(), expected);
+    }
+}
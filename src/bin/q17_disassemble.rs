@@ -0,0 +1,23 @@
+use aoc_2024::file_utils::read_to_string;
+use aoc_2024::q17::disassemble_input;
+
+fn main() {
+    let input = read_to_string("data/q17.txt").expect("Failed to read input");
+    print!("{}", disassemble_input(&input));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_2024::q17::assemble;
+
+    #[test]
+    fn test_disassemble_input_round_trips() {
+        let input = read_to_string("data/q17.txt").expect("Failed to read input");
+        let assembly = disassemble_input(&input);
+        assert_eq!(
+            assemble(&assembly).unwrap(),
+            vec![2, 4, 1, 1, 7, 5, 1, 5, 4, 2, 5, 5, 0, 3, 3, 0]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use aoc_2024::q17::get_total_output_from_computer;
 
 fn main() {
     let input = read_to_string("data/q17.txt").expect("Failed to read input");
-    let result = get_total_output_from_computer(&input);
+    let result = get_total_output_from_computer(&input).expect("Program failed");
     println!(
         "{}",
         result
@@ -22,6 +22,6 @@ mod tests {
     fn test_get_total_output_from_computer() {
         let input = read_to_string("data/q17.txt").expect("Failed to read input");
         let result = get_total_output_from_computer(&input);
-        assert_eq!(result, vec![3, 6, 7, 0, 5, 7, 3, 1, 4]);
+        assert_eq!(result, Ok(vec![3, 6, 7, 0, 5, 7, 3, 1, 4]));
     }
 }
@@ -3,7 +3,7 @@ use aoc_2024::q17::find_a_for_quine_sequence;
 
 fn main() {
     let input = read_to_string("data/q17.txt").expect("Failed to read input");
-    let result = find_a_for_quine_sequence(&input);
+    let result = find_a_for_quine_sequence::<usize>(&input).expect("No solution found");
     println!("{}", result);
 }
 
@@ -14,7 +14,7 @@ mod tests {
     #[test]
     fn test_find_a_for_quine_sequence() {
         let input = read_to_string("data/q17.txt").expect("Failed to read input");
-        let result = find_a_for_quine_sequence(&input);
-        assert_eq!(result, 164278496489149);
+        let result = find_a_for_quine_sequence::<usize>(&input);
+        assert_eq!(result, Ok(164278496489149));
     }
 }
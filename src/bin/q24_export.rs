@@ -0,0 +1,74 @@
+use aoc_2024::q24::{alias_conflicts, export_dot, export_verilog, AliasRules};
+
+// Prints the circuit as Verilog, or as a GraphViz graph when run with `dot`. The graph names wires
+// with the adder alias rules, or with the rules in the file given after `dot`
+fn main() {
+    let input = include_str!("../../data/q24.txt");
+    let mut args = std::env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("dot") => {
+            let rules = match args.next() {
+                Some(path) => load_rules(&path),
+                None => AliasRules::adder(),
+            };
+            for conflict in alias_conflicts(input, &rules) {
+                eprintln!("warning: {}", conflict);
+            }
+            print!("{}", export_dot(input, &rules));
+        }
+        Some("verilog") | None => print!("{}", export_verilog(input)),
+        Some(format) => {
+            eprintln!("unknown format '{}', expected verilog or dot", format);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn load_rules(path: &str) -> AliasRules {
+    let rules = std::fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("cannot read {}: {}", path, error);
+        std::process::exit(1);
+    });
+
+    rules.parse().unwrap_or_else(|error| {
+        eprintln!("{}: {}", path, error);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_dot_aliases() {
+        let input = include_str!("../../data/q24.txt");
+        let dot = export_dot(input, &AliasRules::adder());
+
+        assert!(dot.contains("(CARRY00)"));
+        assert!(dot.contains("(CARRY_INTERMEDIATE01)"));
+        // Aliases follow the carry chain up to the first swapped wire
+        assert!(dot.contains("(CARRY05)"));
+        assert_eq!(dot.matches(" -> ").count(), 222 * 2 + 46);
+
+        let rules = "x{n} XOR y{n} -> HALF_SUM{n}".parse().unwrap();
+        let dot = export_dot(input, &rules);
+        assert_eq!(dot.matches("(HALF_SUM").count(), 45);
+        assert!(!dot.contains("(CARRY"));
+        assert!(alias_conflicts(input, &rules).is_empty());
+    }
+
+    #[test]
+    fn test_export_verilog_ports() {
+        let input = include_str!("../../data/q24.txt");
+        let verilog = export_verilog(input);
+
+        assert_eq!(verilog.matches("input wire").count(), 90);
+        assert_eq!(verilog.matches("output wire").count(), 46);
+        assert_eq!(
+            verilog.lines().filter(|line| line.ends_with(");")).count(),
+            222 + 1
+        );
+    }
+}
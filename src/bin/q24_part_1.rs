@@ -2,7 +2,7 @@ use aoc_2024::q24::compute_z_number;
 
 fn main() {
     let input = include_str!("../../data/q24.txt");
-    let result = compute_z_number(input);
+    let result = compute_z_number(input).expect("Invalid circuit");
     println!("{}", result);
 }
 
@@ -14,6 +14,6 @@ mod tests {
     fn test_compute_z_number() {
         let input = include_str!("../../data/q24.txt");
         let result = compute_z_number(input);
-        assert_eq!(result, 60614602965288);
+        assert_eq!(result, Ok(60614602965288));
     }
 }
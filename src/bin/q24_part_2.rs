@@ -1,11 +1,57 @@
-use aoc_2024::q24::swap_wires;
+use aoc_2024::q24::{find_faulty_wires, pair_swapped_wires, verify_adder};
 
 fn main() {
     let input = include_str!("../../data/q24.txt");
 
-    let wires = swap_wires(input);
+    for fault in verify_adder(input).expect("Failed to verify adder") {
+        println!("{}", fault);
+    }
+
+    let wires = find_faulty_wires(input).expect("Failed to verify adder");
+    if let Some(pairs) = pair_swapped_wires(input, &wires) {
+        for (a, b) in pairs {
+            println!("swap {} and {}", a, b);
+        }
+    }
+
+    println!("{}", wires.join(","));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_2024::q24::check_swaps;
+
+    #[test]
+    fn test_find_faulty_wires() {
+        let input = include_str!("../../data/q24.txt");
+        let wires = find_faulty_wires(input).unwrap();
+        assert_eq!(wires.join(","), "cgr,hpc,hwk,qmd,tnt,z06,z31,z37");
+    }
+
+    #[test]
+    fn test_swaps_fix_the_adder() {
+        let input = include_str!("../../data/q24.txt");
+
+        let counterexample = check_swaps(input, &[]).unwrap().unwrap();
+        assert_eq!(counterexample.lowest_failing_bit(), 6);
+
+        let wires = find_faulty_wires(input).unwrap();
+        let pairs = pair_swapped_wires(input, &wires).unwrap();
+        let swaps = pairs
+            .iter()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .collect::<Vec<_>>();
 
-    for w in wires {
-        println!("{}", w);
+        assert_eq!(
+            swaps,
+            vec![
+                ("cgr", "z37"),
+                ("hpc", "z31"),
+                ("hwk", "z06"),
+                ("qmd", "tnt")
+            ]
+        );
+        assert_eq!(check_swaps(input, &swaps), Ok(None));
     }
 }
@@ -0,0 +1,32 @@
+use aoc_2024::file_utils::read_to_string;
+use aoc_2024::q3::diagnose;
+
+// Prints every call the parser rejected, with the source underlined
+fn main() {
+    let input = read_to_string("data/q3.txt").expect("Error reading file");
+
+    let diagnostics = diagnose(&input);
+    for diagnostic in &diagnostics {
+        println!("{}\n", diagnostic.render(&input));
+    }
+
+    println!("{} rejected calls", diagnostics.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc_2024::q3::diagnostics::DiagnosticKind;
+
+    #[test]
+    fn test_diagnose() {
+        let input = read_to_string("data/q3.txt").expect("Error reading file");
+        let diagnostics = diagnose(&input);
+
+        let unterminated = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.kind == DiagnosticKind::Unterminated)
+            .count();
+        assert_eq!((diagnostics.len(), unterminated), (30, 29));
+    }
+}
@@ -3,7 +3,7 @@ use aoc_2024::q3::compute_multiplication_sum;
 
 fn main() {
     let input = read_to_string("data/q3.txt").expect("Error reading file");
-    let result = compute_multiplication_sum(&input);
+    let result = compute_multiplication_sum(&input).expect("Error running program");
     println!("Result: {}", result);
 }
 
@@ -15,6 +15,6 @@ mod tests {
     fn test_compute_multiplication_sum() {
         let input = read_to_string("data/q3.txt").expect("Error reading file");
         let result = compute_multiplication_sum(&input);
-        assert_eq!(result, 173731097);
+        assert_eq!(result, Ok(173731097));
     }
 }
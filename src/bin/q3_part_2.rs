@@ -3,7 +3,7 @@ use aoc_2024::q3::compute_multiplication_sum_op_aware;
 
 fn main() {
     let input = read_to_string("data/q3.txt").expect("Error reading file");
-    let result = compute_multiplication_sum_op_aware(&input);
+    let result = compute_multiplication_sum_op_aware(&input).expect("Error running program");
     println!("Result: {}", result);
 }
 
@@ -15,6 +15,6 @@ mod tests {
     fn test_compute_multiplication_sum_op_aware() {
         let input = read_to_string("data/q3.txt").expect("Error reading file");
         let result = compute_multiplication_sum_op_aware(&input);
-        assert_eq!(result, 93729253);
+        assert_eq!(result, Ok(93729253));
     }
 }
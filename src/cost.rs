@@ -0,0 +1,44 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::ops::Add;
+
+// The cost of a path in a search. Integer costs keep sums exact so tied paths compare equal,
+// `INFINITY` is only ever compared against and never added to
+pub trait Cost: Copy + PartialOrd + Add<Output = Self> + Debug {
+    const ZERO: Self;
+    const INFINITY: Self;
+
+    fn cmp_cost(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).expect("Costs must be comparable")
+    }
+}
+
+impl Cost for u32 {
+    const ZERO: Self = 0;
+    const INFINITY: Self = u32::MAX;
+}
+
+impl Cost for u64 {
+    const ZERO: Self = 0;
+    const INFINITY: Self = u64::MAX;
+}
+
+impl Cost for usize {
+    const ZERO: Self = 0;
+    const INFINITY: Self = usize::MAX;
+}
+
+impl Cost for i32 {
+    const ZERO: Self = 0;
+    const INFINITY: Self = i32::MAX;
+}
+
+impl Cost for i64 {
+    const ZERO: Self = 0;
+    const INFINITY: Self = i64::MAX;
+}
+
+impl Cost for f64 {
+    const ZERO: Self = 0.0;
+    const INFINITY: Self = f64::INFINITY;
+}
@@ -0,0 +1,324 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+use hashbrown::HashMap;
+
+use crate::a_star_search::SearchResult;
+use crate::cost::Cost;
+
+use super::{Graph, Weight};
+
+// Ordered by f score, the g score is kept to detect stale entries
+struct Elem<K, C>(K, C, C);
+impl<K, C: Cost> PartialEq for Elem<K, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.1 == other.1
+    }
+}
+impl<K, C: Cost> Eq for Elem<K, C> {}
+impl<K, C: Cost> Ord for Elem<K, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.1.cmp_cost(&other.1).reverse()
+    }
+}
+impl<K, C: Cost> PartialOrd for Elem<K, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// One half of a bidirectional search, the forward half walks the edges and the backward
+// half walks the reversed edges from the targets
+struct Frontier<K, C> {
+    dist: HashMap<K, C>,
+    came_from: HashMap<K, K>,
+    open_set: BinaryHeap<Elem<K, C>>,
+}
+
+impl<K, C> Frontier<K, C>
+where
+    K: Eq + Hash + Clone,
+    C: Cost,
+{
+    fn new(roots: impl IntoIterator<Item = (K, C)>) -> Self {
+        let mut frontier = Self {
+            dist: HashMap::new(),
+            came_from: HashMap::new(),
+            open_set: BinaryHeap::new(),
+        };
+
+        for (root, h) in roots {
+            frontier.dist.insert(root.clone(), C::ZERO);
+            frontier.open_set.push(Elem(root, h, C::ZERO));
+        }
+
+        frontier
+    }
+
+    // The smallest f score still queued, stale entries are dropped on the way
+    fn top(&mut self) -> Option<C> {
+        while let Some(Elem(v, f, g)) = self.open_set.peek() {
+            if *g > self.dist[v] {
+                self.open_set.pop();
+                continue;
+            }
+            return Some(*f);
+        }
+
+        None
+    }
+
+    // Follows `came_from` from `node` back to the root it was reached from
+    fn walk_back(&self, node: &K) -> Vec<K> {
+        let mut path = vec![node.clone()];
+        let mut current = node;
+
+        while let Some(next) = self.came_from.get(current) {
+            path.push(next.clone());
+            current = next;
+        }
+
+        path
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+    W: Weight,
+{
+    // Every edge flipped around, so incoming edges can be walked from the target side
+    pub fn reverse_adjacency(&self) -> HashMap<K, Vec<(K, W::Cost)>> {
+        let mut reverse: HashMap<K, Vec<(K, W::Cost)>> = HashMap::with_capacity(self.len());
+
+        for (from, edges) in &self.edges {
+            for (to, w) in edges {
+                reverse
+                    .entry(to.clone())
+                    .or_default()
+                    .push((from.clone(), w.weight()));
+            }
+        }
+
+        reverse
+    }
+
+    pub fn bidirectional_dijkstra(&self, source: K, target: K) -> Option<SearchResult<K, W::Cost>> {
+        self.bidirectional_search(source, &[target], None)
+    }
+
+    // Stops at whichever target is closest, the backward search starts from all of them
+    pub fn bidirectional_dijkstra_any(
+        &self,
+        source: K,
+        targets: &[K],
+    ) -> Option<SearchResult<K, W::Cost>> {
+        self.bidirectional_search(source, targets, None)
+    }
+
+    // The heuristic must be consistent, the backward search uses it towards the source
+    pub fn bidirectional_a_star(
+        &self,
+        source: K,
+        target: K,
+        heuristic: fn(K, K) -> W::Cost,
+    ) -> Option<SearchResult<K, W::Cost>> {
+        self.bidirectional_search(source, &[target], Some(heuristic))
+    }
+
+    fn bidirectional_search(
+        &self,
+        source: K,
+        targets: &[K],
+        heuristic: Option<fn(K, K) -> W::Cost>,
+    ) -> Option<SearchResult<K, W::Cost>> {
+        let reverse = self.reverse_adjacency();
+
+        let forward_h = |v: &K| match heuristic {
+            Some(h) => targets
+                .iter()
+                .map(|t| h(v.clone(), t.clone()))
+                .min_by(|a, b| a.cmp_cost(b))
+                .unwrap_or(W::Cost::ZERO),
+            None => W::Cost::ZERO,
+        };
+        let backward_h = |v: &K| heuristic.map_or(W::Cost::ZERO, |h| h(source.clone(), v.clone()));
+
+        let mut forward = Frontier::new([(source.clone(), forward_h(&source))]);
+        let mut backward = Frontier::new(targets.iter().map(|t| (t.clone(), backward_h(t))));
+
+        let mut expanded = 0;
+        let mut pushed = 1 + targets.len();
+
+        // The cheapest source to target path seen so far and the vertex where both halves met
+        let mut best = W::Cost::INFINITY;
+        let mut meeting = targets.contains(&source).then(|| source.clone());
+        if meeting.is_some() {
+            best = W::Cost::ZERO;
+        }
+
+        while let (Some(top_forward), Some(top_backward)) = (forward.top(), backward.top()) {
+            // Without a heuristic both halves are exact, otherwise either frontier bounds the
+            // remaining paths on its own
+            let done = match heuristic {
+                None => top_forward + top_backward >= best,
+                Some(_) => top_forward >= best || top_backward >= best,
+            };
+            if done {
+                break;
+            }
+
+            let expand_forward = top_forward.cmp_cost(&top_backward) != Ordering::Greater;
+            let (frontier, other) = if expand_forward {
+                (&mut forward, &backward)
+            } else {
+                (&mut backward, &forward)
+            };
+            let h = |v: &K| {
+                if expand_forward {
+                    forward_h(v)
+                } else {
+                    backward_h(v)
+                }
+            };
+
+            let Elem(u, _, g) = frontier.open_set.pop().unwrap();
+            expanded += 1;
+
+            let neighbours = if expand_forward {
+                self.edges
+                    .get(&u)
+                    .map(|edges| edges.iter().map(|(v, w)| (v.clone(), w.weight())).collect())
+                    .unwrap_or_default()
+            } else {
+                reverse.get(&u).cloned().unwrap_or_default()
+            };
+
+            for (v, w) in neighbours {
+                let alt = g + w;
+                if alt < *frontier.dist.get(&v).unwrap_or(&W::Cost::INFINITY) {
+                    frontier.dist.insert(v.clone(), alt);
+                    frontier.came_from.insert(v.clone(), u.clone());
+                    frontier.open_set.push(Elem(v.clone(), alt + h(&v), alt));
+                    pushed += 1;
+
+                    if let Some(d) = other.dist.get(&v) {
+                        if alt + *d < best {
+                            best = alt + *d;
+                            meeting = Some(v);
+                        }
+                    }
+                }
+            }
+        }
+
+        let meeting = meeting?;
+
+        let mut path = forward.walk_back(&meeting);
+        path.reverse();
+        path.extend(backward.walk_back(&meeting).into_iter().skip(1));
+
+        Some(SearchResult {
+            path,
+            cost: best,
+            expanded,
+            pushed,
+            closed: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star_search::AStarSearch;
+
+    fn path_cost(graph: &Graph<(i32, i32), u32>, path: &[(i32, i32)]) -> u32 {
+        path.windows(2)
+            .map(|pair| {
+                graph.edges[&pair[0]]
+                    .iter()
+                    .find(|(v, _)| *v == pair[1])
+                    .unwrap()
+                    .1
+            })
+            .sum()
+    }
+
+    fn manhattan(a: (i32, i32), b: (i32, i32)) -> u32 {
+        a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+    }
+
+    // A directed grid where moving right or down costs 1 and moving left or up costs 3
+    fn grid_graph(size: i32) -> Graph<(i32, i32), u32> {
+        let mut graph = Graph::new();
+        for i in 0..size {
+            for j in 0..size {
+                for (di, dj, w) in [(0, 1, 1), (1, 0, 1), (0, -1, 3), (-1, 0, 3)] {
+                    let (ni, nj) = (i + di, j + dj);
+                    if (0..size).contains(&ni) && (0..size).contains(&nj) {
+                        graph.add_edge((i, j), (ni, nj), w);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_bidirectional_dijkstra_matches_dijkstra() {
+        let graph = grid_graph(15);
+        let (dist, _) = graph.dijkstra((7, 7));
+
+        for target in [(0, 0), (14, 14), (3, 11), (7, 7)] {
+            let result = graph.bidirectional_dijkstra((7, 7), target).unwrap();
+
+            assert_eq!(result.cost, dist[&target]);
+            assert_eq!(result.path.first(), Some(&(7, 7)));
+            assert_eq!(result.path.last(), Some(&target));
+            assert_eq!(path_cost(&graph, &result.path), result.cost);
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_a_star_matches_dijkstra() {
+        let graph = grid_graph(15);
+        let (dist, _) = graph.dijkstra((2, 3));
+
+        for target in [(0, 0), (14, 14), (12, 1)] {
+            let result = graph
+                .bidirectional_a_star((2, 3), target, manhattan)
+                .unwrap();
+
+            assert_eq!(result.cost, dist[&target]);
+            assert_eq!(path_cost(&graph, &result.path), result.cost);
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_dijkstra_expands_fewer_vertices() {
+        let graph = grid_graph(40);
+        let one_sided = graph
+            .a_star_search_where((0, 0), |v| *v == (39, 39), |_| 0, false)
+            .unwrap();
+        let two_sided = graph.bidirectional_dijkstra((0, 0), (39, 39)).unwrap();
+
+        assert_eq!(two_sided.cost, one_sided.cost);
+        assert!(two_sided.expanded < one_sided.expanded);
+    }
+
+    #[test]
+    fn test_bidirectional_dijkstra_follows_edge_direction() {
+        let mut graph = Graph::new();
+        graph.add_edge('a', 'b', 1u32);
+        graph.add_edge('b', 'c', 1);
+        graph.add_edge('c', 'a', 1);
+
+        assert_eq!(graph.bidirectional_dijkstra('a', 'c').unwrap().cost, 2);
+        assert_eq!(graph.bidirectional_dijkstra('c', 'b').unwrap().cost, 2);
+
+        graph.add_edge('d', 'a', 1);
+        assert!(graph.bidirectional_dijkstra('a', 'd').is_none());
+    }
+}
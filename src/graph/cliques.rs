@@ -0,0 +1,395 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+use hashbrown::HashSet;
+
+use super::Graph;
+
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    // Both slices are sorted, so a merge is enough
+    let mut result = Vec::with_capacity(a.len().min(b.len()));
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                result.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    result
+}
+
+fn difference(a: &[usize], b: &[usize]) -> Vec<usize> {
+    a.iter()
+        .filter(|v| b.binary_search(v).is_err())
+        .copied()
+        .collect()
+}
+
+fn insert_sorted(a: &mut Vec<usize>, v: usize) {
+    if let Err(i) = a.binary_search(&v) {
+        a.insert(i, v);
+    }
+}
+
+fn remove_sorted(a: &mut Vec<usize>, v: usize) {
+    if let Ok(i) = a.binary_search(&v) {
+        a.remove(i);
+    }
+}
+
+// Repeatedly removes the vertex of smallest remaining degree, ties are broken by index
+fn degeneracy_ordering(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let mut degree = adjacency.iter().map(|n| n.len()).collect::<Vec<_>>();
+    let mut removed = vec![false; adjacency.len()];
+    let mut ordering = Vec::with_capacity(adjacency.len());
+
+    let mut queue = (0..adjacency.len())
+        .map(|v| Reverse((degree[v], v)))
+        .collect::<BinaryHeap<_>>();
+
+    while let Some(Reverse((d, v))) = queue.pop() {
+        if removed[v] || d != degree[v] {
+            continue;
+        }
+
+        removed[v] = true;
+        ordering.push(v);
+
+        for &u in &adjacency[v] {
+            if !removed[u] {
+                degree[u] -= 1;
+                queue.push(Reverse((degree[u], u)));
+            }
+        }
+    }
+
+    ordering
+}
+
+struct Frame {
+    r: Vec<usize>,
+    p: Vec<usize>,
+    x: Vec<usize>,
+    candidates: Vec<usize>,
+    next: usize,
+}
+
+impl Frame {
+    fn new(r: Vec<usize>, p: Vec<usize>, x: Vec<usize>, adjacency: &[Vec<usize>]) -> Self {
+        // Tomita pivot: the vertex of P ∪ X with the most neighbours in P
+        let pivot = p
+            .iter()
+            .chain(x.iter())
+            .max_by_key(|&&u| (intersect(&p, &adjacency[u]).len(), Reverse(u)))
+            .copied();
+
+        let candidates = match pivot {
+            Some(u) => difference(&p, &adjacency[u]),
+            None => p.clone(),
+        };
+
+        Self {
+            r,
+            p,
+            x,
+            candidates,
+            next: 0,
+        }
+    }
+}
+
+// Streams every maximal clique using Bron–Kerbosch with pivoting over a degeneracy ordering.
+// Each clique is yielded once, sorted, and the order of the cliques only depends on the graph
+pub struct MaximalCliques<K> {
+    keys: Vec<K>,
+    adjacency: Vec<Vec<usize>>,
+    ordering: Vec<usize>,
+    position: Vec<usize>,
+    outer: usize,
+    stack: Vec<Frame>,
+}
+
+impl<K: Clone> MaximalCliques<K> {
+    fn clique(&self, mut r: Vec<usize>) -> Vec<K> {
+        r.sort_unstable();
+        r.into_iter().map(|v| self.keys[v].clone()).collect()
+    }
+}
+
+impl<K: Clone> Iterator for MaximalCliques<K> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(frame) = self.stack.last_mut() else {
+                let &v = self.ordering.get(self.outer)?;
+                self.outer += 1;
+
+                let (later, earlier): (Vec<usize>, Vec<usize>) = self.adjacency[v]
+                    .iter()
+                    .partition(|&&u| self.position[u] > self.position[v]);
+
+                if later.is_empty() && earlier.is_empty() {
+                    return Some(self.clique(vec![v]));
+                }
+
+                if !later.is_empty() {
+                    let frame = Frame::new(vec![v], later, earlier, &self.adjacency);
+                    self.stack.push(frame);
+                }
+
+                continue;
+            };
+
+            let Some(&v) = frame.candidates.get(frame.next) else {
+                self.stack.pop();
+                continue;
+            };
+            frame.next += 1;
+
+            let mut r = frame.r.clone();
+            r.push(v);
+            let p = intersect(&frame.p, &self.adjacency[v]);
+            let x = intersect(&frame.x, &self.adjacency[v]);
+
+            remove_sorted(&mut frame.p, v);
+            insert_sorted(&mut frame.x, v);
+
+            if p.is_empty() && x.is_empty() {
+                return Some(self.clique(r));
+            }
+
+            if !p.is_empty() {
+                let frame = Frame::new(r, p, x, &self.adjacency);
+                self.stack.push(frame);
+            }
+        }
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone + Ord,
+{
+    // Same as `undirected_adjacency` but with the vertices in sorted order, so that
+    // indices compare the same way as the keys and the adjacency lists are sorted
+    fn sorted_undirected_adjacency(&self) -> (Vec<K>, Vec<Vec<usize>>) {
+        let (keys, adjacency) = self.undirected_adjacency();
+
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| keys[a].cmp(&keys[b]));
+
+        let mut rank = vec![0; keys.len()];
+        for (i, &v) in order.iter().enumerate() {
+            rank[v] = i;
+        }
+
+        let sorted_keys = order.iter().map(|&v| keys[v].clone()).collect();
+        let sorted_adjacency = order
+            .iter()
+            .map(|&v| {
+                let mut neighbours = adjacency[v].iter().map(|&u| rank[u]).collect::<Vec<_>>();
+                neighbours.sort_unstable();
+                neighbours
+            })
+            .collect();
+
+        (sorted_keys, sorted_adjacency)
+    }
+
+    pub fn maximal_cliques(&self) -> MaximalCliques<K> {
+        let (keys, adjacency) = self.sorted_undirected_adjacency();
+        let ordering = degeneracy_ordering(&adjacency);
+
+        let mut position = vec![0; keys.len()];
+        for (i, &v) in ordering.iter().enumerate() {
+            position[v] = i;
+        }
+
+        MaximalCliques {
+            keys,
+            adjacency,
+            ordering,
+            position,
+            outer: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    // Calls `f` with every clique of exactly `k` vertices, each clique is visited once and is sorted
+    pub fn for_each_k_clique<F>(&self, k: usize, mut f: F)
+    where
+        F: FnMut(&[K]),
+    {
+        if k == 0 {
+            return;
+        }
+
+        let (keys, adjacency) = self.sorted_undirected_adjacency();
+
+        // Only extend cliques with larger vertices, so each clique is built in ascending order once
+        let forward = adjacency
+            .iter()
+            .enumerate()
+            .map(|(v, neighbours)| {
+                neighbours
+                    .iter()
+                    .filter(|&&u| u > v)
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        fn extend<K: Clone, F: FnMut(&[K])>(
+            keys: &[K],
+            forward: &[Vec<usize>],
+            k: usize,
+            candidates: &[usize],
+            clique: &mut Vec<K>,
+            f: &mut F,
+        ) {
+            if clique.len() == k {
+                f(clique);
+                return;
+            }
+
+            for (i, &v) in candidates.iter().enumerate() {
+                if clique.len() + candidates.len() - i < k {
+                    break;
+                }
+
+                clique.push(keys[v].clone());
+                let next = intersect(&candidates[i + 1..], &forward[v]);
+                extend(keys, forward, k, &next, clique, f);
+                clique.pop();
+            }
+        }
+
+        let all = (0..keys.len()).collect::<Vec<_>>();
+        let mut clique = Vec::with_capacity(k);
+        extend(&keys, &forward, k, &all, &mut clique, &mut f);
+    }
+
+    pub fn count_k_cliques_where<P>(&self, k: usize, predicate: P) -> usize
+    where
+        P: Fn(&[K]) -> bool,
+    {
+        let mut count = 0;
+
+        self.for_each_k_clique(k, |clique| {
+            if predicate(clique) {
+                count += 1;
+            }
+        });
+
+        count
+    }
+
+    // All cliques of exactly `k` vertices, each sorted and listed in lexicographic order
+    pub fn k_cliques(&self, k: usize) -> Vec<Vec<K>> {
+        let mut cliques = Vec::new();
+        self.for_each_k_clique(k, |clique| cliques.push(clique.to_vec()));
+        cliques
+    }
+
+    // The largest maximal clique, ties are broken by the lexicographically smallest clique
+    pub fn bron_kerbosh(&self) -> HashSet<K> {
+        self.maximal_cliques()
+            .max_by(|a, b| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected(edges: &[(u32, u32)]) -> Graph<u32> {
+        let mut graph = Graph::new();
+        for &(u, v) in edges {
+            graph.add_edge(u, v, ());
+            graph.add_edge(v, u, ());
+        }
+        graph
+    }
+
+    // A 4-clique {1, 2, 3, 4}, a triangle {4, 5, 6} sharing vertex 4 and a pendant edge 6-7
+    const EDGES: [(u32, u32); 10] = [
+        (1, 2),
+        (1, 3),
+        (1, 4),
+        (2, 3),
+        (2, 4),
+        (3, 4),
+        (4, 5),
+        (4, 6),
+        (5, 6),
+        (6, 7),
+    ];
+
+    #[test]
+    fn test_maximal_cliques() {
+        let graph = undirected(&EDGES);
+        let mut cliques = graph.maximal_cliques().collect::<Vec<_>>();
+        cliques.sort();
+
+        assert_eq!(cliques, vec![vec![1, 2, 3, 4], vec![4, 5, 6], vec![6, 7]]);
+    }
+
+    #[test]
+    fn test_maximal_cliques_is_deterministic() {
+        // Vertices are taken in degeneracy order of the sorted keys, whatever order the edges
+        // were added in
+        let expected = vec![vec![6, 7], vec![4, 5, 6], vec![1, 2, 3, 4]];
+        let mut reversed = EDGES;
+        reversed.reverse();
+
+        for edges in [EDGES, reversed] {
+            let cliques = undirected(&edges).maximal_cliques().collect::<Vec<_>>();
+            assert_eq!(cliques, expected);
+        }
+    }
+
+    #[test]
+    fn test_k_cliques() {
+        let graph = undirected(&EDGES);
+
+        assert_eq!(
+            graph.k_cliques(3),
+            vec![
+                vec![1, 2, 3],
+                vec![1, 2, 4],
+                vec![1, 3, 4],
+                vec![2, 3, 4],
+                vec![4, 5, 6]
+            ]
+        );
+        assert_eq!(graph.k_cliques(4), vec![vec![1, 2, 3, 4]]);
+        assert!(graph.k_cliques(5).is_empty());
+    }
+
+    #[test]
+    fn test_count_k_cliques_where() {
+        let graph = undirected(&EDGES);
+
+        assert_eq!(graph.count_k_cliques_where(3, |c| c.contains(&4)), 4);
+        assert_eq!(graph.count_k_cliques_where(2, |_| true), EDGES.len());
+    }
+
+    #[test]
+    fn test_bron_kerbosh() {
+        let graph = undirected(&EDGES);
+
+        assert_eq!(graph.bron_kerbosh(), HashSet::from([1, 2, 3, 4]));
+    }
+}
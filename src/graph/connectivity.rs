@@ -0,0 +1,278 @@
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+
+use super::Graph;
+
+const UNVISITED: usize = usize::MAX;
+
+// The result of a single Tarjan style low-link pass, expressed in indices into `keys`
+struct LowLink {
+    bridges: Vec<(usize, usize)>,
+    articulation_points: Vec<usize>,
+    components: Vec<Vec<(usize, usize)>>,
+}
+
+struct Frame {
+    vertex: usize,
+    parent: usize,
+    next: usize,
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+{
+    // Maps every vertex to a dense index and builds a simple undirected adjacency list,
+    // an edge stored in either (or both) directions is treated as a single undirected edge
+    pub(crate) fn undirected_adjacency(&self) -> (Vec<K>, Vec<Vec<usize>>) {
+        let keys = self.vertices.iter().cloned().collect::<Vec<_>>();
+        let index = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (k.clone(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut neighbours = vec![HashSet::new(); keys.len()];
+
+        for (from, edges) in &self.edges {
+            let u = index[from];
+            for (to, _) in edges {
+                let v = index[to];
+                if u != v {
+                    neighbours[u].insert(v);
+                    neighbours[v].insert(u);
+                }
+            }
+        }
+
+        let adjacency = neighbours
+            .into_iter()
+            .map(|n| n.into_iter().collect())
+            .collect();
+
+        (keys, adjacency)
+    }
+
+    fn low_link(adjacency: &[Vec<usize>]) -> LowLink {
+        let n = adjacency.len();
+        let mut disc = vec![UNVISITED; n];
+        let mut low = vec![UNVISITED; n];
+        let mut is_articulation = vec![false; n];
+        let mut time = 0;
+
+        let mut bridges = Vec::new();
+        let mut components = Vec::new();
+        let mut edge_stack = Vec::new();
+
+        for root in 0..n {
+            if disc[root] != UNVISITED {
+                continue;
+            }
+
+            disc[root] = time;
+            low[root] = time;
+            time += 1;
+
+            let mut root_children = 0;
+
+            // Iterative DFS, grids produce graphs far too deep for recursion
+            let mut stack = vec![Frame {
+                vertex: root,
+                parent: UNVISITED,
+                next: 0,
+            }];
+
+            while let Some(frame) = stack.last_mut() {
+                let v = frame.vertex;
+                let parent = frame.parent;
+
+                if let Some(&w) = adjacency[v].get(frame.next) {
+                    frame.next += 1;
+
+                    if w == parent {
+                        continue;
+                    }
+
+                    if disc[w] == UNVISITED {
+                        disc[w] = time;
+                        low[w] = time;
+                        time += 1;
+                        edge_stack.push((v, w));
+
+                        if v == root {
+                            root_children += 1;
+                        }
+
+                        stack.push(Frame {
+                            vertex: w,
+                            parent: v,
+                            next: 0,
+                        });
+                    } else if disc[w] < disc[v] {
+                        low[v] = low[v].min(disc[w]);
+                        edge_stack.push((v, w));
+                    }
+
+                    continue;
+                }
+
+                stack.pop();
+
+                if parent == UNVISITED {
+                    continue;
+                }
+
+                low[parent] = low[parent].min(low[v]);
+
+                if low[v] > disc[parent] {
+                    bridges.push((parent, v));
+                }
+
+                if low[v] >= disc[parent] {
+                    if parent != root {
+                        is_articulation[parent] = true;
+                    }
+
+                    let mut component = Vec::new();
+                    while let Some(edge) = edge_stack.pop() {
+                        component.push(edge);
+                        if edge == (parent, v) {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+
+            if root_children > 1 {
+                is_articulation[root] = true;
+            }
+        }
+
+        let articulation_points = (0..n).filter(|&v| is_articulation[v]).collect();
+
+        LowLink {
+            bridges,
+            articulation_points,
+            components,
+        }
+    }
+
+    // Edges whose removal disconnects the graph, the graph is treated as undirected
+    pub fn bridges(&self) -> Vec<(K, K)> {
+        let (keys, adjacency) = self.undirected_adjacency();
+
+        Self::low_link(&adjacency)
+            .bridges
+            .into_iter()
+            .map(|(u, v)| (keys[u].clone(), keys[v].clone()))
+            .collect()
+    }
+
+    // Vertices whose removal disconnects the graph, the graph is treated as undirected
+    pub fn articulation_points(&self) -> HashSet<K> {
+        let (keys, adjacency) = self.undirected_adjacency();
+
+        Self::low_link(&adjacency)
+            .articulation_points
+            .into_iter()
+            .map(|v| keys[v].clone())
+            .collect()
+    }
+
+    // Maximal subgraphs without an articulation point, returned as the vertices of each component.
+    // Articulation points belong to every component they join, isolated vertices belong to none
+    pub fn biconnected_components(&self) -> Vec<HashSet<K>> {
+        let (keys, adjacency) = self.undirected_adjacency();
+
+        Self::low_link(&adjacency)
+            .components
+            .into_iter()
+            .map(|component| {
+                component
+                    .into_iter()
+                    .flat_map(|(u, v)| [keys[u].clone(), keys[v].clone()])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undirected(edges: &[(u32, u32)]) -> Graph<u32> {
+        let mut graph = Graph::new();
+        for &(u, v) in edges {
+            graph.add_edge(u, v, ());
+            graph.add_edge(v, u, ());
+        }
+        graph
+    }
+
+    // Two triangles joined by the bridge 3-4, with a pendant vertex 7 hanging off 6
+    const EDGES: [(u32, u32); 8] = [
+        (1, 2),
+        (2, 3),
+        (3, 1),
+        (3, 4),
+        (4, 5),
+        (5, 6),
+        (6, 4),
+        (6, 7),
+    ];
+
+    #[test]
+    fn test_bridges() {
+        let graph = undirected(&EDGES);
+
+        let mut bridges = graph
+            .bridges()
+            .into_iter()
+            .map(|(u, v)| (u.min(v), u.max(v)))
+            .collect::<Vec<_>>();
+        bridges.sort();
+
+        assert_eq!(bridges, vec![(3, 4), (6, 7)]);
+    }
+
+    #[test]
+    fn test_articulation_points() {
+        let graph = undirected(&EDGES);
+
+        assert_eq!(graph.articulation_points(), HashSet::from([3, 4, 6]));
+    }
+
+    #[test]
+    fn test_biconnected_components() {
+        let graph = undirected(&EDGES);
+
+        let mut components = graph
+            .biconnected_components()
+            .into_iter()
+            .map(|c| {
+                let mut c = c.into_iter().collect::<Vec<_>>();
+                c.sort();
+                c
+            })
+            .collect::<Vec<_>>();
+        components.sort();
+
+        assert_eq!(
+            components,
+            vec![vec![1, 2, 3], vec![3, 4], vec![4, 5, 6], vec![6, 7]]
+        );
+    }
+
+    #[test]
+    fn test_single_direction_edges_are_undirected() {
+        let mut graph = Graph::new();
+        graph.add_edge(1, 2, ());
+        graph.add_edge(2, 3, ());
+
+        assert_eq!(graph.articulation_points(), HashSet::from([2]));
+        assert_eq!(graph.bridges().len(), 2);
+    }
+}
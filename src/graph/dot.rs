@@ -0,0 +1,232 @@
+use std::fmt::Display;
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+
+use super::Graph;
+
+type Label<'a, T> = Box<dyn Fn(&T) -> String + 'a>;
+
+// Renders a `Graph` in the GraphViz DOT language, created through `Graph::dot`
+pub struct Dot<'a, K, W> {
+    graph: &'a Graph<K, W>,
+    undirected: bool,
+    vertex_label: Option<Label<'a, K>>,
+    // Edges are unlabelled unless given a label, such as their weight
+    edge_label: Option<Label<'a, W>>,
+    highlighted_vertices: HashSet<K>,
+    highlighted_edges: HashSet<(K, K)>,
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<'a, K, W> Dot<'a, K, W>
+where
+    K: Eq + Hash + Clone,
+{
+    // Emits a `graph` with a single `--` edge for every pair of vertices connected in either direction
+    pub fn undirected(mut self) -> Self {
+        self.undirected = true;
+        self
+    }
+
+    pub fn vertex_label(mut self, label: impl Fn(&K) -> String + 'a) -> Self {
+        self.vertex_label = Some(Box::new(label));
+        self
+    }
+
+    pub fn edge_label(mut self, label: impl Fn(&W) -> String + 'a) -> Self {
+        self.edge_label = Some(Box::new(label));
+        self
+    }
+
+    pub fn highlight_path(mut self, path: &[K]) -> Self {
+        self.highlighted_vertices.extend(path.iter().cloned());
+        for pair in path.windows(2) {
+            self.highlighted_edges
+                .insert((pair[0].clone(), pair[1].clone()));
+        }
+        self
+    }
+
+    // Highlights every vertex and edge of `subgraph`, such as the one returned by `shortest_paths_subgraph`
+    pub fn highlight_subgraph<V>(mut self, subgraph: &Graph<K, V>) -> Self {
+        self.highlighted_vertices
+            .extend(subgraph.vertices.iter().cloned());
+        for (from, edges) in &subgraph.edges {
+            for (to, _) in edges {
+                self.highlighted_edges.insert((from.clone(), to.clone()));
+            }
+        }
+        self
+    }
+
+    fn is_highlighted(&self, from: &K, to: &K) -> bool {
+        self.highlighted_edges.contains(&(from.clone(), to.clone()))
+            || (self.undirected && self.highlighted_edges.contains(&(to.clone(), from.clone())))
+    }
+}
+
+impl<K, W> Dot<'_, K, W>
+where
+    K: Eq + Hash + Clone,
+    W: Display,
+{
+    pub fn weight_labels(mut self) -> Self {
+        self.edge_label = Some(Box::new(|weight| weight.to_string()));
+        self
+    }
+}
+
+// Vertices are numbered in key order, so the same graph always gives the same output
+impl<K, W> Display for Dot<'_, K, W>
+where
+    K: Eq + Hash + Clone + Ord + Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (keyword, connector) = if self.undirected {
+            ("graph", "--")
+        } else {
+            ("digraph", "->")
+        };
+
+        writeln!(f, "{} {{", keyword)?;
+
+        // DOT identifiers are generated, the keys are only ever used as labels
+        let mut vertices = self.graph.vertices.iter().collect::<Vec<_>>();
+        vertices.sort();
+        let mut ids = HashMap::with_capacity(self.graph.len());
+
+        for vertex in vertices.iter().copied() {
+            let id = ids.len();
+            ids.insert(vertex, id);
+
+            let label = match &self.vertex_label {
+                Some(label) => label(vertex),
+                None => vertex.to_string(),
+            };
+
+            write!(f, "    n{} [label=\"{}\"", id, escape(&label))?;
+            if self.highlighted_vertices.contains(vertex) {
+                write!(f, ", color=red, penwidth=2")?;
+            }
+            writeln!(f, "];")?;
+        }
+
+        let mut written = HashSet::new();
+
+        for from in vertices.iter().copied() {
+            for (to, weight) in self.graph.edges.get(from).into_iter().flatten() {
+                let (a, b) = (ids[from], ids[to]);
+
+                if self.undirected && !written.insert((a.min(b), a.max(b))) {
+                    continue;
+                }
+
+                let label = self
+                    .edge_label
+                    .as_ref()
+                    .map_or_else(String::new, |label| label(weight));
+
+                write!(f, "    n{} {} n{}", a, connector, b)?;
+
+                let mut attributes = Vec::with_capacity(2);
+                if !label.is_empty() {
+                    attributes.push(format!("label=\"{}\"", escape(&label)));
+                }
+                if self.is_highlighted(from, to) {
+                    attributes.push("color=red, penwidth=2".to_string());
+                }
+
+                if !attributes.is_empty() {
+                    write!(f, " [{}]", attributes.join(", "))?;
+                }
+                writeln!(f, ";")?;
+            }
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn dot(&self) -> Dot<'_, K, W> {
+        Dot {
+            graph: self,
+            undirected: false,
+            vertex_label: None,
+            edge_label: None,
+            highlighted_vertices: HashSet::new(),
+            highlighted_edges: HashSet::new(),
+        }
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone + Ord + Display,
+    W: Display,
+{
+    pub fn to_dot(&self) -> String {
+        self.dot().weight_labels().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", 1);
+
+        let dot = graph.to_dot();
+
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("[label=\"a\"];"));
+        assert!(dot.contains("[label=\"b\"];"));
+        assert!(dot.contains(" -> ") && dot.contains("[label=\"1\"];"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_dot_undirected_with_highlighted_path() {
+        let mut graph = Graph::new();
+        for (u, v) in [("a", "b"), ("b", "c"), ("a", "c")] {
+            graph.add_edge(u, v, 1);
+            graph.add_edge(v, u, 1);
+        }
+
+        let dot = graph
+            .dot()
+            .undirected()
+            .vertex_label(|v| v.to_uppercase())
+            .edge_label(|_| String::new())
+            .highlight_path(&["a", "b"])
+            .to_string();
+
+        assert!(dot.starts_with("graph {\n"));
+        assert_eq!(dot.matches(" -- ").count(), 3);
+        assert_eq!(dot.matches("color=red").count(), 3);
+        assert!(dot.contains("[label=\"C\"];"));
+    }
+
+    #[test]
+    fn test_dot_unit_weights_in_key_order() {
+        let mut graph: Graph<&str> = Graph::new();
+        for (u, v) in [("c", "a"), ("b", "c"), ("a", "b")] {
+            graph.add_edge(u, v, ());
+        }
+
+        assert_eq!(
+            graph.dot().to_string(),
+            "digraph {\n    n0 [label=\"a\"];\n    n1 [label=\"b\"];\n    n2 [label=\"c\"];\n    n0 -> n1;\n    n1 -> n2;\n    n2 -> n0;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use super::Graph;
+
+// The edge-list format has one tab separated `from<TAB>to<TAB>weight` edge per line.
+// A line with a single field is a vertex without outgoing edges, blank lines and
+// lines starting with `#` are ignored. Tabs are used so keys may contain spaces, keys and
+// weights that would read back differently are refused when writing
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdgeListError {
+    InvalidVertex { line: usize, value: String },
+    InvalidWeight { line: usize, value: String },
+    MissingWeight { line: usize },
+    TooManyFields { line: usize },
+    // Empty, blank, starting with `#`, or containing a tab or line break
+    UnwritableKey { value: String },
+    UnwritableWeight { value: String },
+}
+
+impl Display for EdgeListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidVertex { line, value } => {
+                write!(f, "line {}: invalid vertex '{}'", line, value)
+            }
+            Self::InvalidWeight { line, value } => {
+                write!(f, "line {}: invalid weight '{}'", line, value)
+            }
+            Self::MissingWeight { line } => write!(f, "line {}: missing weight", line),
+            Self::TooManyFields { line } => write!(f, "line {}: too many fields", line),
+            Self::UnwritableKey { value } => write!(f, "key {:?} cannot be written", value),
+            Self::UnwritableWeight { value } => {
+                write!(f, "weight {:?} cannot be written", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EdgeListError {}
+
+fn breaks_fields(value: &str) -> bool {
+    value.contains(['\t', '\n', '\r'])
+}
+
+fn writable_key(key: impl Display) -> Result<String, EdgeListError> {
+    let value = key.to_string();
+    if value.trim().is_empty() || value.starts_with('#') || breaks_fields(&value) {
+        return Err(EdgeListError::UnwritableKey { value });
+    }
+
+    Ok(value)
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone + Ord + Display,
+    W: Display,
+{
+    // Vertices and the edges out of each vertex are written in key order, so the same graph
+    // always gives the same file
+    pub fn to_edge_list(&self) -> Result<String, EdgeListError> {
+        let mut output = String::new();
+
+        let mut vertices = self.vertices.iter().collect::<Vec<_>>();
+        vertices.sort();
+
+        for vertex in vertices {
+            let from = writable_key(vertex)?;
+
+            match self.edges.get(vertex) {
+                Some(edges) if !edges.is_empty() => {
+                    let mut edges = edges.iter().collect::<Vec<_>>();
+                    edges.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                    for (to, weight) in edges {
+                        let weight = weight.to_string();
+                        if breaks_fields(&weight) {
+                            return Err(EdgeListError::UnwritableWeight { value: weight });
+                        }
+
+                        writeln!(output, "{}\t{}\t{}", from, writable_key(to)?, weight).unwrap();
+                    }
+                }
+                _ => writeln!(output, "{}", from).unwrap(),
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone + FromStr,
+    W: FromStr,
+{
+    pub fn from_edge_list(input: &str) -> Result<Self, EdgeListError> {
+        let mut graph = Graph::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim_end_matches('\r');
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parse_vertex = |value: &str| {
+                value
+                    .parse::<K>()
+                    .map_err(|_| EdgeListError::InvalidVertex {
+                        line: line_number,
+                        value: value.to_string(),
+                    })
+            };
+
+            let fields = line.split('\t').collect::<Vec<_>>();
+
+            match fields.as_slice() {
+                [vertex] => {
+                    graph.vertices.insert(parse_vertex(vertex)?);
+                }
+                [_, _] => return Err(EdgeListError::MissingWeight { line: line_number }),
+                [from, to, weight] => {
+                    let weight = weight
+                        .parse::<W>()
+                        .map_err(|_| EdgeListError::InvalidWeight {
+                            line: line_number,
+                            value: weight.to_string(),
+                        })?;
+                    graph.add_edge(parse_vertex(from)?, parse_vertex(to)?, weight);
+                }
+                _ => return Err(EdgeListError::TooManyFields { line: line_number }),
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_list_round_trip() {
+        let mut graph = Graph::new();
+        graph.add_edge("a b".to_string(), "c".to_string(), 1.5);
+        graph.add_edge("c".to_string(), "a b".to_string(), 2.0);
+        graph.vertices.insert("d".to_string());
+
+        let reloaded =
+            Graph::<String, f64>::from_edge_list(&graph.to_edge_list().unwrap()).unwrap();
+
+        assert_eq!(reloaded.vertices, graph.vertices);
+        assert_eq!(reloaded.edges, graph.edges);
+    }
+
+    #[test]
+    fn test_to_edge_list_is_sorted_by_key() {
+        let mut graph = Graph::new();
+        graph.add_edge(3, 1, "c");
+        graph.add_edge(1, 3, "b");
+        graph.add_edge(1, 2, "a");
+        graph.add_edge(1, 2, "d");
+        graph.vertices.insert(0);
+
+        assert_eq!(
+            graph.to_edge_list().unwrap(),
+            "0\n1\t2\ta\n1\t2\td\n1\t3\tb\n2\n3\t1\tc\n"
+        );
+    }
+
+    #[test]
+    fn test_to_edge_list_refuses_keys_that_do_not_read_back() {
+        for key in ["", " ", "#a", "a\tb", "a\r"] {
+            let mut graph: Graph<String, u32> = Graph::new();
+            graph.vertices.insert(key.to_string());
+
+            assert_eq!(
+                graph.to_edge_list(),
+                Err(EdgeListError::UnwritableKey {
+                    value: key.to_string()
+                })
+            );
+        }
+
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", "1\n2");
+        assert_eq!(
+            graph.to_edge_list(),
+            Err(EdgeListError::UnwritableWeight {
+                value: "1\n2".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_edge_list_errors() {
+        let result = Graph::<u32, u32>::from_edge_list("# comment\n1\t2\t3\n\n2\t3\tx");
+        assert_eq!(
+            result.unwrap_err(),
+            EdgeListError::InvalidWeight {
+                line: 4,
+                value: "x".to_string()
+            }
+        );
+
+        let result = Graph::<u32, u32>::from_edge_list("1\t2");
+        assert_eq!(
+            result.unwrap_err(),
+            EdgeListError::MissingWeight { line: 1 }
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use hashbrown::HashSet;
+
+use crate::cost::Cost;
+use crate::grid::{Grid, Tile};
+
+use super::{Graph, Weight};
+
+type Cell = (usize, usize);
+
+// The weight of an edge built from a grid. For contracted corridors `cells` holds the
+// cells walked between the two endpoints (exclusive), so the full path can be recovered
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corridor<C> {
+    pub cost: C,
+    pub cells: Vec<Cell>,
+}
+
+impl<C: Cost> Weight for Corridor<C> {
+    type Cost = C;
+
+    fn weight(&self) -> C {
+        self.cost
+    }
+}
+
+impl<C: std::fmt::Display> std::fmt::Display for Corridor<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.cost)
+    }
+}
+
+type CostFn<'a, C> = Box<dyn Fn(Cell, Cell) -> C + 'a>;
+
+// Turns a `Grid` of tiles into a `Graph` over the traversable cells
+pub struct GridGraphBuilder<'a, K: Clone + Tile> {
+    grid: &'a Grid<K>,
+    diagonals: bool,
+    cost: Option<CostFn<'a, K::Cost>>,
+    contract_corridors: bool,
+    keep: HashSet<Cell>,
+}
+
+impl<'a, K> GridGraphBuilder<'a, K>
+where
+    K: Clone + Tile,
+{
+    pub fn new(grid: &'a Grid<K>) -> Self {
+        Self {
+            grid,
+            diagonals: false,
+            cost: None,
+            contract_corridors: false,
+            keep: HashSet::new(),
+        }
+    }
+
+    pub fn diagonals(mut self, diagonals: bool) -> Self {
+        self.diagonals = diagonals;
+        self
+    }
+
+    // Overrides the edge cost, by default this is `Tile::cost_from` of the destination tile
+    pub fn cost(mut self, cost: impl Fn(Cell, Cell) -> K::Cost + 'a) -> Self {
+        self.cost = Some(Box::new(cost));
+        self
+    }
+
+    // Replaces chains of degree 2 cells with a single edge carrying the summed cost
+    pub fn contract_corridors(mut self, contract: bool) -> Self {
+        self.contract_corridors = contract;
+        self
+    }
+
+    // Cells that must survive contraction, such as the start and the goal
+    pub fn keep(mut self, cell: Cell) -> Self {
+        self.keep.insert(cell);
+        self
+    }
+
+    fn edge_cost(&self, from: Cell, to: Cell) -> K::Cost {
+        match &self.cost {
+            Some(cost) => cost(from, to),
+            None => self.grid[to].cost_from(self.grid[from].clone()),
+        }
+    }
+
+    pub fn build(self) -> Graph<Cell, Corridor<K::Cost>> {
+        let mut graph = Graph::with_capacity(self.grid.rows * self.grid.cols);
+
+        for row in 0..self.grid.rows {
+            for col in 0..self.grid.cols {
+                let from = (row, col);
+                if !self.grid[from].traversable() {
+                    continue;
+                }
+
+                graph.vertices.insert(from);
+
+                for to in self.grid.adjacent_indices(from, self.diagonals) {
+                    if self.grid[to].traversable() {
+                        let corridor = Corridor {
+                            cost: self.edge_cost(from, to),
+                            cells: Vec::new(),
+                        };
+                        graph.add_edge(from, to, corridor);
+                    }
+                }
+            }
+        }
+
+        if self.contract_corridors {
+            self.contract(&graph)
+        } else {
+            graph
+        }
+    }
+
+    fn is_junction(&self, graph: &Graph<Cell, Corridor<K::Cost>>, cell: &Cell) -> bool {
+        self.keep.contains(cell) || graph.degree(cell) != 2
+    }
+
+    fn contract(&self, graph: &Graph<Cell, Corridor<K::Cost>>) -> Graph<Cell, Corridor<K::Cost>> {
+        let mut contracted = Graph::new();
+
+        // Cycles made only of corridor cells have no junction to start from and are dropped
+        for from in graph.vertices.iter().filter(|v| self.is_junction(graph, v)) {
+            contracted.vertices.insert(*from);
+
+            for (next, corridor) in graph.edges.get(from).into_iter().flatten() {
+                let mut previous = *from;
+                let mut current = *next;
+                let mut cost = corridor.cost;
+                let mut cells = Vec::new();
+
+                while !self.is_junction(graph, &current) {
+                    cells.push(current);
+
+                    let (next, corridor) = graph.edges[&current]
+                        .iter()
+                        .find(|(v, _)| *v != previous)
+                        .expect("Corridor cells have exactly two neighbours");
+
+                    cost = cost + corridor.cost;
+                    previous = current;
+                    current = *next;
+                }
+
+                if current != *from {
+                    contracted.add_edge(*from, current, Corridor { cost, cells });
+                }
+            }
+        }
+
+        contracted
+    }
+}
+
+impl<C: Cost> Graph<Cell, Corridor<C>> {
+    // Expands a path through a contracted graph back into every cell walked
+    pub fn expand_path(&self, path: &[Cell]) -> Vec<Cell> {
+        let mut cells = Vec::with_capacity(path.len());
+
+        for pair in path.windows(2) {
+            cells.push(pair[0]);
+
+            // Parallel corridors between the same junctions are possible, take the cheapest
+            let corridor = self.edges[&pair[0]]
+                .iter()
+                .filter(|(v, _)| *v == pair[1])
+                .map(|(_, c)| c)
+                .min_by(|a, b| a.cost.cmp_cost(&b.cost))
+                .expect("Path follows the edges of the graph");
+
+            cells.extend_from_slice(&corridor.cells);
+        }
+
+        cells.extend(path.last());
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star_search::AStarSearch;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Cave(bool);
+
+    impl Tile for Cave {
+        fn traversable(&self) -> bool {
+            self.0
+        }
+
+        type Cost = u32;
+
+        fn cost_from<V>(&self, _other: V) -> u32
+        where
+            V: Tile,
+        {
+            1
+        }
+    }
+
+    const MAZE: &str = "
+#########
+#S..#...#
+###.#.#.#
+#...#.#.#
+#.###.#.#
+#.....#E#
+#########";
+
+    fn parse(input: &str) -> (Grid<Cave>, Cell, Cell) {
+        let mut grid = Grid::new();
+        let (mut start, mut goal) = ((0, 0), (0, 0));
+
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(j, c)| {
+                    match c {
+                        'S' => start = (grid.rows, j),
+                        'E' => goal = (grid.rows, j),
+                        _ => {}
+                    }
+                    Cave(c != '#')
+                })
+                .collect::<Vec<_>>();
+            grid.push(&row);
+        }
+
+        (grid, start, goal)
+    }
+
+    fn path_cost(graph: &Graph<Cell, Corridor<u32>>, path: &[Cell]) -> u32 {
+        path.windows(2)
+            .map(|p| {
+                graph.edges[&p[0]]
+                    .iter()
+                    .filter(|(v, _)| *v == p[1])
+                    .map(|(_, c)| c.cost)
+                    .min()
+                    .unwrap()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_build() {
+        let (grid, _, _) = parse(MAZE);
+        let graph = GridGraphBuilder::new(&grid).build();
+
+        assert_eq!(graph.len(), 23);
+        assert_eq!(graph.degree(&(1, 3)), 2);
+    }
+
+    #[test]
+    fn test_contract_corridors() {
+        fn no_heuristic(_: Cell, _: Cell) -> u32 {
+            0
+        }
+
+        let (grid, start, goal) = parse(MAZE);
+
+        let full = GridGraphBuilder::new(&grid).build();
+        let contracted = GridGraphBuilder::new(&grid)
+            .contract_corridors(true)
+            .keep(start)
+            .keep(goal)
+            .build();
+
+        // The maze is a single corridor, so only the two ends remain
+        assert_eq!(contracted.len(), 2);
+
+        let full_path = full.a_star_search(start, goal, no_heuristic).unwrap();
+        let contracted_path = contracted.a_star_search(start, goal, no_heuristic).unwrap();
+
+        assert_eq!(
+            path_cost(&contracted, &contracted_path),
+            path_cost(&full, &full_path)
+        );
+        assert_eq!(contracted.expand_path(&contracted_path), full_path);
+    }
+}
@@ -0,0 +1,360 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Index;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::a_star_search::AStarSearch;
+use crate::cost::Cost;
+
+mod bidirectional;
+mod cliques;
+mod connectivity;
+mod dot;
+mod edge_list;
+mod from_grid;
+mod spanning_tree;
+
+pub use from_grid::{Corridor, GridGraphBuilder};
+
+pub trait Weight {
+    type Cost: Cost;
+
+    fn weight(&self) -> Self::Cost;
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<K, W = ()> {
+    pub vertices: HashSet<K>,
+    pub edges: HashMap<K, Vec<(K, W)>>,
+}
+
+impl<K, W> Graph<K, W> {
+    pub fn new() -> Self {
+        Self {
+            vertices: HashSet::new(),
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<K, W> Default for Graph<K, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, W> Graph<K, W> {
+    pub fn with_capacity(vertices: usize) -> Self {
+        Self {
+            vertices: HashSet::with_capacity(vertices),
+            edges: HashMap::with_capacity(vertices),
+        }
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn add_edge(&mut self, from: K, to: K, weight: W) {
+        self.vertices.insert(from.clone());
+        self.vertices.insert(to.clone());
+        self.edges.entry(from).or_default().push((to, weight));
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, vertex: &K) -> bool {
+        self.vertices.contains(vertex)
+    }
+
+    pub fn remove_vertex(&mut self, vertex: K) {
+        self.vertices.remove(&vertex);
+        self.edges.remove(&vertex);
+        for (_, edges) in self.edges.iter_mut() {
+            edges.retain(|(v, _)| v != &vertex);
+        }
+    }
+
+    pub fn degree(&self, vertex: &K) -> usize {
+        self.edges.get(vertex).map_or(0, |edges| edges.len())
+    }
+}
+
+impl<K, W> Index<K> for Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+{
+    type Output = Vec<(K, W)>;
+
+    fn index(&self, index: K) -> &Self::Output {
+        &self.edges[&index]
+    }
+}
+
+impl<K, W> Graph<K, W> {
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+}
+
+impl<K, W> std::fmt::Display for Graph<K, W>
+where
+    K: std::fmt::Display,
+    W: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (vertex, edges) in &self.edges {
+            write!(f, "{} -> ", vertex)?;
+            for (to, weight) in edges {
+                write!(f, "({} -> {}), ", to, weight)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Weight for f64 {
+    type Cost = f64;
+
+    fn weight(&self) -> f64 {
+        *self
+    }
+}
+
+impl Weight for i32 {
+    type Cost = i32;
+
+    fn weight(&self) -> i32 {
+        *self
+    }
+}
+
+impl Weight for u32 {
+    type Cost = u32;
+
+    fn weight(&self) -> u32 {
+        *self
+    }
+}
+
+impl Weight for u64 {
+    type Cost = u64;
+
+    fn weight(&self) -> u64 {
+        *self
+    }
+}
+
+impl Weight for usize {
+    type Cost = usize;
+
+    fn weight(&self) -> usize {
+        *self
+    }
+}
+
+impl Weight for f32 {
+    type Cost = f64;
+
+    fn weight(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl Weight for i64 {
+    type Cost = i64;
+
+    fn weight(&self) -> i64 {
+        *self
+    }
+}
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+    W: Weight,
+{
+    // Returns the distance to every vertex (`Cost::INFINITY` when unreachable) and a prev graph
+    // holding, for every vertex, an edge to each predecessor on one of its shortest paths
+    pub fn dijkstra(&self, source: K) -> (HashMap<K, W::Cost>, Graph<K, W::Cost>) {
+        let mut dist = HashMap::with_capacity(self.len());
+        let mut prev = Graph::with_capacity(self.len()); // The prev graph will always be shorter than the original graph, so we can use the same capacity
+
+        let mut queue = BinaryHeap::new();
+
+        pub struct Elem<K, C> {
+            vertex: K,
+            priority: C,
+        }
+
+        impl<K, C: Cost> PartialEq for Elem<K, C> {
+            fn eq(&self, other: &Self) -> bool {
+                self.priority == other.priority
+            }
+        }
+
+        impl<K, C: Cost> Eq for Elem<K, C> {}
+
+        impl<K, C: Cost> PartialOrd for Elem<K, C> {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<K, C: Cost> Ord for Elem<K, C> {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.priority.cmp_cost(&other.priority)
+            }
+        }
+
+        for vertex in &self.vertices {
+            dist.insert(vertex.clone(), W::Cost::INFINITY);
+        }
+
+        queue.push(Reverse(Elem {
+            vertex: source.clone(),
+            priority: W::Cost::ZERO,
+        }));
+
+        dist.insert(source.clone(), W::Cost::ZERO);
+
+        while let Some(Reverse(Elem {
+            vertex: u,
+            priority,
+        })) = queue.pop()
+        {
+            if priority > dist[&u] {
+                continue;
+            }
+
+            if let Some(edges) = self.edges.get(&u) {
+                for (v, w) in edges {
+                    let alt = priority + w.weight();
+                    match alt.cmp_cost(&dist[v]) {
+                        std::cmp::Ordering::Less => {
+                            dist.insert(v.clone(), alt);
+                            prev.edges.remove(v);
+                            prev.add_edge(v.clone(), u.clone(), alt);
+                            queue.push(Reverse(Elem {
+                                vertex: v.clone(),
+                                priority: alt,
+                            }));
+                        }
+                        std::cmp::Ordering::Equal => {
+                            // Another shortest path, the distance is unchanged so v needs no revisit
+                            let known =
+                                prev.edges.get(v).is_some_and(|edges: &Vec<(K, W::Cost)>| {
+                                    edges.iter().any(|(p, _)| p == &u)
+                                });
+                            if !known {
+                                prev.add_edge(v.clone(), u.clone(), alt);
+                            }
+                        }
+                        std::cmp::Ordering::Greater => {}
+                    }
+                }
+            }
+        }
+
+        (dist, prev)
+    }
+}
+
+impl<K, C> Graph<K, C>
+where
+    K: Clone + Eq + Hash,
+    C: Cost,
+{
+    // This assumes that the graph is a DAG and that the source and target vertices are in the graph
+    // And that the graph is strongly connected, such as the prev graph returned by dijkstra
+    pub fn shortest_paths_subgraph(self, source: K, target: K) -> Graph<K, C> {
+        let mut graph = Graph::new();
+
+        let mut queue = vec![source.clone()];
+        let mut seen: HashSet<K> = HashSet::from_iter([source]);
+
+        while let Some(e) = queue.pop() {
+            if e == target {
+                continue;
+            }
+
+            let mut minimum_weight = C::INFINITY;
+            let mut minimum_weight_vertices = Vec::new();
+
+            if let Some(edges) = self.edges.get(&e) {
+                for (v, w) in edges.iter() {
+                    match w.cmp_cost(&minimum_weight) {
+                        std::cmp::Ordering::Less => {
+                            minimum_weight = *w;
+                            minimum_weight_vertices = vec![v.clone()];
+                        }
+                        std::cmp::Ordering::Equal => minimum_weight_vertices.push(v.clone()),
+                        std::cmp::Ordering::Greater => {}
+                    }
+                }
+            }
+
+            for v in minimum_weight_vertices {
+                graph.add_edge(e.clone(), v.clone(), minimum_weight);
+                if seen.insert(v.clone()) {
+                    queue.push(v);
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+impl<K, W> AStarSearch for Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+    W: Weight + Debug + Clone,
+{
+    type Node = K;
+    type Cost = W::Cost;
+
+    fn weighted_neighbours(&self, node: &Self::Node) -> Option<Vec<(Self::Node, W::Cost)>> {
+        self.edges
+            .get(node)
+            .map(|edges| edges.iter().map(|(v, w)| (v.clone(), w.weight())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dijkstra_records_tied_predecessors() {
+        // Two routes of cost 3 from a to d, and a more expensive one through e
+        let mut graph = Graph::new();
+        for (u, v, w) in [
+            ("a", "b", 1u32),
+            ("b", "d", 2),
+            ("a", "c", 2),
+            ("c", "d", 1),
+            ("a", "e", 1),
+            ("e", "d", 5),
+        ] {
+            graph.add_edge(u, v, w);
+        }
+
+        let (dist, prev) = graph.dijkstra("a");
+
+        assert_eq!(dist["d"], 3);
+        assert_eq!(dist["e"], 1);
+
+        let subgraph = prev.shortest_paths_subgraph("d", "a");
+        assert_eq!(subgraph.vertices, HashSet::from(["a", "b", "c", "d"]));
+    }
+}
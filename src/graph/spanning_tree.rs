@@ -0,0 +1,176 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+use hashbrown::HashMap;
+
+use crate::cost::Cost;
+use crate::union_find::UnionFind;
+
+use super::{Graph, Weight};
+
+// Both algorithms treat the graph as undirected and return a minimum spanning forest,
+// one tree per connected component, with every tree edge stored in both directions
+
+impl<K, W> Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+    W: Weight + Clone,
+{
+    fn indexed_vertices(&self) -> (Vec<K>, HashMap<K, usize>) {
+        let keys = self.vertices.iter().cloned().collect::<Vec<_>>();
+        let index = keys
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (k.clone(), i))
+            .collect();
+        (keys, index)
+    }
+
+    fn forest(&self, keys: &[K], tree_edges: Vec<(usize, usize, W)>) -> Graph<K, W> {
+        let mut forest = Graph::with_capacity(keys.len());
+        forest.vertices.extend(keys.iter().cloned());
+
+        for (u, v, w) in tree_edges {
+            forest.add_edge(keys[u].clone(), keys[v].clone(), w.clone());
+            forest.add_edge(keys[v].clone(), keys[u].clone(), w);
+        }
+
+        forest
+    }
+
+    pub fn kruskal(&self) -> Graph<K, W> {
+        let (keys, index) = self.indexed_vertices();
+        let index = &index;
+
+        let mut edges = self
+            .edges
+            .iter()
+            .flat_map(|(from, edges)| {
+                let u = index[from];
+                edges.iter().map(move |(to, w)| (u, index[to], w))
+            })
+            .collect::<Vec<_>>();
+
+        edges.sort_by(|a, b| a.2.weight().cmp_cost(&b.2.weight()));
+
+        let mut union_find = UnionFind::new(keys.len());
+        let mut tree_edges = Vec::with_capacity(keys.len().saturating_sub(1));
+
+        for (u, v, w) in edges {
+            if union_find.union(u, v) {
+                tree_edges.push((u, v, w.clone()));
+            }
+        }
+
+        self.forest(&keys, tree_edges)
+    }
+
+    pub fn prim(&self) -> Graph<K, W> {
+        struct Elem<C>(C, usize, usize);
+        impl<C: Cost> PartialEq for Elem<C> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl<C: Cost> Eq for Elem<C> {}
+        impl<C: Cost> Ord for Elem<C> {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp_cost(&other.0)
+            }
+        }
+        impl<C: Cost> PartialOrd for Elem<C> {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        let (keys, index) = self.indexed_vertices();
+
+        // Edges are only stored on the source vertex, so mirror them to walk both ways
+        let mut adjacency = vec![Vec::new(); keys.len()];
+        for (from, edges) in &self.edges {
+            let u = index[from];
+            for (to, w) in edges {
+                let v = index[to];
+                adjacency[u].push((v, w));
+                adjacency[v].push((u, w));
+            }
+        }
+
+        let mut in_tree = vec![false; keys.len()];
+        let mut tree_edges = Vec::with_capacity(keys.len().saturating_sub(1));
+        let mut queue = BinaryHeap::new();
+
+        for root in 0..keys.len() {
+            if in_tree[root] {
+                continue;
+            }
+
+            in_tree[root] = true;
+            for (i, (_, w)) in adjacency[root].iter().enumerate() {
+                queue.push(Reverse(Elem(w.weight(), root, i)));
+            }
+
+            while let Some(Reverse(Elem(_, u, i))) = queue.pop() {
+                let (v, w) = adjacency[u][i];
+                if in_tree[v] {
+                    continue;
+                }
+
+                in_tree[v] = true;
+                tree_edges.push((u, v, w.clone()));
+
+                for (j, (x, w)) in adjacency[v].iter().enumerate() {
+                    if !in_tree[*x] {
+                        queue.push(Reverse(Elem(w.weight(), v, j)));
+                    }
+                }
+            }
+        }
+
+        self.forest(&keys, tree_edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn total_weight(forest: &Graph<&str, u32>) -> u32 {
+        // Every tree edge is stored twice
+        forest.edges.values().flatten().map(|(_, w)| w).sum::<u32>() / 2
+    }
+
+    fn graph() -> Graph<&'static str, u32> {
+        let mut graph = Graph::new();
+        for (u, v, w) in [
+            ("a", "b", 4),
+            ("a", "c", 1),
+            ("b", "c", 2),
+            ("b", "d", 5),
+            ("c", "d", 8),
+            ("e", "f", 3),
+        ] {
+            graph.add_edge(u, v, w);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_kruskal() {
+        let forest = graph().kruskal();
+
+        assert_eq!(forest.len(), 6);
+        assert_eq!(total_weight(&forest), 11);
+        assert!(forest.edges["a"].contains(&("c", 1)));
+        assert!(forest.edges["e"].contains(&("f", 3)));
+    }
+
+    #[test]
+    fn test_prim_matches_kruskal() {
+        let graph = graph();
+
+        assert_eq!(total_weight(&graph.prim()), total_weight(&graph.kruskal()));
+    }
+}
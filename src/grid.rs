@@ -4,7 +4,7 @@ use std::{
     ops::{BitOr, BitOrAssign, Index, IndexMut},
 };
 
-use crate::{a_star_search::AStarSearch, vec2::Vec2};
+use crate::{a_star_search::AStarSearch, cost::Cost, vec2::Vec2};
 
 #[derive(Default, Clone)]
 pub struct Grid<K: Clone> {
@@ -349,8 +349,10 @@ impl Grid<bool> {
 }
 
 pub trait Tile {
+    type Cost: Cost;
+
     fn traversable(&self) -> bool;
-    fn cost_from<V>(&self, other: V) -> f64
+    fn cost_from<V>(&self, other: V) -> Self::Cost
     where
         V: Tile;
 }
@@ -360,8 +362,9 @@ where
     K: Clone + Tile + Eq + Hash,
 {
     type Node = (usize, usize);
+    type Cost = K::Cost;
 
-    fn weighted_neighbours(&self, node: &Self::Node) -> Option<Vec<(Self::Node, f64)>> {
+    fn weighted_neighbours(&self, node: &Self::Node) -> Option<Vec<(Self::Node, K::Cost)>> {
         let mut neighbours = Vec::new();
         let tile = self.get(*node)?;
         for delta in self.deltas(*node, false) {
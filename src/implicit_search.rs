@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::a_star_search::{AStarSearch, SearchResult};
+use crate::cost::Cost;
+
+// A graph that is never materialised, neighbours are generated on demand by `successors`.
+// Any hashable state works as a node, so puzzles can search (position, direction, ...) tuples directly
+pub struct StateSpace<N, C, F> {
+    successors: F,
+    _marker: PhantomData<(N, C)>,
+}
+
+impl<N, C, F> AStarSearch for StateSpace<N, C, F>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    F: Fn(&N) -> Vec<(N, C)>,
+{
+    type Node = N;
+    type Cost = C;
+
+    fn weighted_neighbours(&self, node: &N) -> Option<Vec<(N, C)>> {
+        Some((self.successors)(node))
+    }
+}
+
+impl<N, C, F> StateSpace<N, C, F>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    F: Fn(&N) -> Vec<(N, C)>,
+{
+    pub fn new(successors: F) -> Self {
+        Self {
+            successors,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn dijkstra<P>(&self, start: N, is_goal: P) -> Option<SearchResult<N, C>>
+    where
+        P: Fn(&N) -> bool,
+    {
+        self.a_star_search_where(start, is_goal, |_| C::ZERO, false)
+    }
+
+    pub fn a_star<P, H>(&self, start: N, is_goal: P, heuristic: H) -> Option<SearchResult<N, C>>
+    where
+        P: Fn(&N) -> bool,
+        H: Fn(&N) -> C,
+    {
+        self.a_star_search_where(start, is_goal, heuristic, false)
+    }
+
+    // Ignores the edge costs, the returned cost is the number of steps taken
+    pub fn bfs<P>(&self, start: N, is_goal: P) -> Option<SearchResult<N, usize>>
+    where
+        P: Fn(&N) -> bool,
+    {
+        let mut came_from = HashMap::new();
+        let mut seen: HashSet<N> = HashSet::from_iter([start.clone()]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut expanded = 0;
+
+        while let Some((v, steps)) = queue.pop_front() {
+            if is_goal(&v) {
+                return Some(SearchResult {
+                    path: self.reconstruct_path(came_from, v),
+                    cost: steps,
+                    expanded,
+                    pushed: seen.len(),
+                    closed: None,
+                });
+            }
+
+            expanded += 1;
+
+            for (u, _) in (self.successors)(&v) {
+                if seen.insert(u.clone()) {
+                    came_from.insert(u.clone(), v.clone());
+                    queue.push_back((u, steps + 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Edges must cost either zero or one fixed non-zero amount, zero cost edges are explored first
+    pub fn zero_one_bfs<P>(&self, start: N, is_goal: P) -> Option<SearchResult<N, C>>
+    where
+        P: Fn(&N) -> bool,
+    {
+        let mut came_from = HashMap::new();
+        let mut dist: HashMap<N, C> = HashMap::from_iter([(start.clone(), C::ZERO)]);
+        let mut queue = VecDeque::from([(start, C::ZERO)]);
+        let mut expanded = 0;
+        let mut pushed = 1;
+
+        while let Some((v, d)) = queue.pop_front() {
+            if d > dist[&v] {
+                continue;
+            }
+
+            if is_goal(&v) {
+                return Some(SearchResult {
+                    path: self.reconstruct_path(came_from, v),
+                    cost: d,
+                    expanded,
+                    pushed,
+                    closed: None,
+                });
+            }
+
+            expanded += 1;
+
+            for (u, w) in (self.successors)(&v) {
+                let alt = d + w;
+                if alt < *dist.get(&u).unwrap_or(&C::INFINITY) {
+                    dist.insert(u.clone(), alt);
+                    came_from.insert(u.clone(), v.clone());
+                    pushed += 1;
+
+                    if w == C::ZERO {
+                        queue.push_front((u, alt));
+                    } else {
+                        queue.push_back((u, alt));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Walk along the number line: +1 costs 1, doubling is free, never go past 20
+    fn successors(n: &u32) -> Vec<(u32, u32)> {
+        [(n + 1, 1), (n * 2, 0)]
+            .into_iter()
+            .filter(|(m, _)| *m <= 20 && m != n)
+            .collect()
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let space = StateSpace::new(successors);
+        let result = space.dijkstra(1, |n| *n == 20).unwrap();
+
+        // 1 -> 2 -> 4 -> 5 -> 10 -> 20
+        assert_eq!(result.cost, 1);
+        assert_eq!(result.path.first(), Some(&1));
+        assert_eq!(result.path.last(), Some(&20));
+    }
+
+    #[test]
+    fn test_zero_one_bfs_matches_dijkstra() {
+        let space = StateSpace::new(successors);
+
+        for goal in 1..=20 {
+            let dijkstra = space.dijkstra(1, |n| *n == goal).unwrap();
+            let zero_one = space.zero_one_bfs(1, |n| *n == goal).unwrap();
+            assert_eq!(zero_one.cost, dijkstra.cost);
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let space = StateSpace::new(successors);
+        let result = space.bfs(1, |n| *n == 20).unwrap();
+
+        // 1 -> 2 -> 4 -> 5 -> 10 -> 20 is also the fewest steps
+        assert_eq!(result.cost, 5);
+        assert_eq!(result.path.len(), 6);
+    }
+
+    #[test]
+    fn test_a_star() {
+        let space = StateSpace::new(|n: &(i32, i32)| {
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .into_iter()
+                .map(|(dx, dy)| ((n.0 + dx, n.1 + dy), 1))
+                .filter(|((x, y), _)| x.abs() <= 5 && y.abs() <= 5)
+                .collect()
+        });
+
+        let goal = (3, -4);
+        let result = space
+            .a_star(
+                (0, 0),
+                |n| *n == goal,
+                |n| (n.0 - goal.0).abs() + (n.1 - goal.1).abs(),
+            )
+            .unwrap();
+
+        assert_eq!(result.cost, 7);
+        assert!(space.dijkstra((0, 0), |n| *n == (6, 0)).is_none());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::a_star_search::SearchResult;
+use crate::grid::{Grid, Tile};
+use crate::implicit_search::StateSpace;
+
+type Cell = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Heading {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Heading {
+    const ALL: [Self; 4] = [Self::North, Self::South, Self::East, Self::West];
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Self::North => (-1, 0),
+            Self::South => (1, 0),
+            Self::East => (0, 1),
+            Self::West => (0, -1),
+        }
+    }
+
+    fn is_horizontal(self) -> bool {
+        matches!(self, Self::East | Self::West)
+    }
+}
+
+// A jump point together with the heading it was reached with, the heading decides which
+// directions are worth exploring from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct JumpPoint {
+    cell: Cell,
+    heading: Option<Heading>,
+}
+
+fn offset((row, col): Cell, heading: Heading) -> (isize, isize) {
+    let (dr, dc) = heading.delta();
+    (row as isize + dr, col as isize + dc)
+}
+
+// Jump point search on a 4-connected grid where every step costs the same. Vertical moves
+// play the role of the diagonal moves in the classic 8-connected algorithm: every vertical
+// step scans horizontally, and horizontal scans only stop at the goal or where a vertical
+// move is forced by an obstacle behind it
+impl<K> Grid<K>
+where
+    K: Clone + Tile,
+{
+    fn open(&self, (row, col): (isize, isize)) -> bool {
+        row >= 0
+            && col >= 0
+            && (row as usize) < self.rows
+            && (col as usize) < self.cols
+            && self[(row as usize, col as usize)].traversable()
+    }
+
+    fn forced_verticals(&self, cell: Cell, heading: Heading) -> Vec<Heading> {
+        let (behind_row, behind_col) = offset(cell, heading);
+        let behind = (
+            2 * cell.0 as isize - behind_row,
+            2 * cell.1 as isize - behind_col,
+        );
+
+        [Heading::North, Heading::South]
+            .into_iter()
+            .filter(|v| {
+                let (dr, dc) = v.delta();
+                self.open(offset(cell, *v)) && !self.open((behind.0 + dr, behind.1 + dc))
+            })
+            .collect()
+    }
+
+    fn jump_horizontal(&self, from: Cell, heading: Heading, goal: Cell) -> Option<(Cell, usize)> {
+        let mut cell = from;
+        let mut distance = 0;
+
+        loop {
+            let next = offset(cell, heading);
+            if !self.open(next) {
+                return None;
+            }
+
+            cell = (next.0 as usize, next.1 as usize);
+            distance += 1;
+
+            if cell == goal || !self.forced_verticals(cell, heading).is_empty() {
+                return Some((cell, distance));
+            }
+        }
+    }
+
+    fn jump_vertical(&self, from: Cell, heading: Heading, goal: Cell) -> Option<(Cell, usize)> {
+        let mut cell = from;
+        let mut distance = 0;
+
+        loop {
+            let next = offset(cell, heading);
+            if !self.open(next) {
+                return None;
+            }
+
+            cell = (next.0 as usize, next.1 as usize);
+            distance += 1;
+
+            if cell == goal {
+                return Some((cell, distance));
+            }
+
+            if [Heading::East, Heading::West]
+                .into_iter()
+                .any(|h| self.jump_horizontal(cell, h, goal).is_some())
+            {
+                return Some((cell, distance));
+            }
+        }
+    }
+
+    fn jump_successors(&self, point: &JumpPoint, goal: Cell) -> Vec<(JumpPoint, usize)> {
+        let headings = match point.heading {
+            None => Heading::ALL.to_vec(),
+            Some(h) if h.is_horizontal() => {
+                let mut headings = vec![h];
+                headings.extend(self.forced_verticals(point.cell, h));
+                headings
+            }
+            Some(v) => vec![v, Heading::East, Heading::West],
+        };
+
+        headings
+            .into_iter()
+            .filter_map(|heading| {
+                let jump = if heading.is_horizontal() {
+                    self.jump_horizontal(point.cell, heading, goal)
+                } else {
+                    self.jump_vertical(point.cell, heading, goal)
+                };
+
+                jump.map(|(cell, distance)| {
+                    let next = JumpPoint {
+                        cell,
+                        heading: Some(heading),
+                    };
+                    (next, distance)
+                })
+            })
+            .collect()
+    }
+
+    // Returns the same path length as `a_star_search` with unit step costs, the path is
+    // expanded back into every cell and `expanded` counts the jump points expanded
+    pub fn jump_point_search(&self, start: Cell, goal: Cell) -> Option<SearchResult<Cell, usize>> {
+        if !self.open((start.0 as isize, start.1 as isize)) {
+            return None;
+        }
+
+        let space = StateSpace::new(|point: &JumpPoint| self.jump_successors(point, goal));
+
+        let start = JumpPoint {
+            cell: start,
+            heading: None,
+        };
+
+        let result = space.a_star(
+            start,
+            |point| point.cell == goal,
+            |point| point.cell.0.abs_diff(goal.0) + point.cell.1.abs_diff(goal.1),
+        )?;
+
+        let mut path = vec![result.path[0].cell];
+        for pair in result.path.windows(2) {
+            let (to, heading) = (pair[1].cell, pair[1].heading.unwrap());
+            let mut cell = pair[0].cell;
+            while cell != to {
+                let next = offset(cell, heading);
+                cell = (next.0 as usize, next.1 as usize);
+                path.push(cell);
+            }
+        }
+
+        Some(SearchResult {
+            path,
+            cost: result.cost,
+            expanded: result.expanded,
+            pushed: result.pushed,
+            closed: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::a_star_search::AStarSearch;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Floor(bool);
+
+    impl Tile for Floor {
+        type Cost = usize;
+
+        fn traversable(&self) -> bool {
+            self.0
+        }
+
+        fn cost_from<V>(&self, _other: V) -> usize
+        where
+            V: Tile,
+        {
+            1
+        }
+    }
+
+    fn manhattan(a: Cell, b: Cell) -> usize {
+        a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+    }
+
+    // Small linear congruential generator so the random grids are the same on every run
+    fn random_grid(seed: u64, rows: usize, cols: usize, density: u64) -> Grid<Floor> {
+        let mut state = seed;
+        let mut grid = Grid::with_capacity_and_default(rows, cols, Floor(true));
+
+        for row in 0..rows {
+            for col in 0..cols {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                if (state >> 33) % 100 < density {
+                    grid[(row, col)] = Floor(false);
+                }
+            }
+        }
+
+        grid[(0, 0)] = Floor(true);
+        grid[(rows - 1, cols - 1)] = Floor(true);
+        grid
+    }
+
+    fn assert_valid_path(grid: &Grid<Floor>, path: &[Cell]) {
+        for pair in path.windows(2) {
+            assert_eq!(manhattan(pair[0], pair[1]), 1);
+            assert!(grid[pair[1]].traversable());
+        }
+    }
+
+    fn assert_matches_a_star(grid: &Grid<Floor>, start: Cell, goal: Cell) {
+        let a_star = grid.a_star_search_result(start, goal, manhattan);
+        let jps = grid.jump_point_search(start, goal);
+
+        assert_eq!(
+            a_star.as_ref().map(|r| r.cost),
+            jps.as_ref().map(|r| r.cost),
+            "{:?} to {:?}",
+            start,
+            goal
+        );
+
+        if let Some(jps) = jps {
+            assert_eq!(jps.path.len(), jps.cost + 1);
+            assert_eq!(jps.path.first(), Some(&start));
+            assert_eq!(jps.path.last(), Some(&goal));
+            assert_valid_path(grid, &jps.path);
+        }
+    }
+
+    #[test]
+    fn test_jump_point_search_matches_a_star() {
+        for seed in 0..200 {
+            let (rows, cols) = (5 + (seed as usize % 13), 5 + (seed as usize % 7));
+            let grid = random_grid(seed, rows, cols, 10 + seed % 30);
+            assert_matches_a_star(&grid, (0, 0), (rows - 1, cols - 1));
+        }
+    }
+
+    #[test]
+    fn test_jump_point_search_between_random_cells() {
+        let mut state = 17u64;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize % bound
+        };
+
+        for seed in 0..100 {
+            let (rows, cols) = (6 + (seed as usize % 11), 6 + (seed as usize % 9));
+            let mut grid = random_grid(seed, rows, cols, 15 + seed % 25);
+
+            for _ in 0..10 {
+                let start = (next(rows), next(cols));
+                let goal = (next(rows), next(cols));
+                grid[start] = Floor(true);
+                grid[goal] = Floor(true);
+
+                assert_matches_a_star(&grid, start, goal);
+            }
+        }
+    }
+
+    #[test]
+    fn test_jump_point_search_around_walls() {
+        // The start is boxed in behind two walls, the way out runs away from the goal first
+        let rows = [
+            "G.........",
+            "..........",
+            "#########.",
+            "..........",
+            ".#########",
+            "..........",
+            "....S.....",
+        ];
+        let mut grid = Grid::with_capacity_and_default(rows.len(), rows[0].len(), Floor(true));
+        let mut cells = [(0, 0); 2];
+
+        for (row, line) in rows.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                match c {
+                    '#' => grid[(row, col)] = Floor(false),
+                    'S' => cells[0] = (row, col),
+                    'G' => cells[1] = (row, col),
+                    _ => {}
+                }
+            }
+        }
+
+        let [start, goal] = cells;
+        assert_matches_a_star(&grid, start, goal);
+        assert_matches_a_star(&grid, goal, start);
+        assert_eq!(grid.jump_point_search(start, goal).unwrap().cost, 28);
+    }
+
+    #[test]
+    fn test_jump_point_search_expands_fewer_nodes() {
+        let grid = Grid::with_capacity_and_default(50, 50, Floor(true));
+        let (start, goal) = ((0, 0), (49, 49));
+
+        let a_star = grid.a_star_search_result(start, goal, manhattan).unwrap();
+        let jps = grid.jump_point_search(start, goal).unwrap();
+
+        assert_eq!(jps.cost, a_star.cost);
+        assert!(jps.expanded < a_star.expanded);
+    }
+}
@@ -1,8 +1,12 @@
-mod a_star_search;
+pub mod a_star_search;
 mod branch_magic;
+pub mod cost;
 pub mod file_utils;
-mod graph;
-mod grid;
+pub mod graph;
+pub mod grid;
+pub mod implicit_search;
+pub mod jump_point_search;
+pub mod lpa_star;
 pub mod q1;
 pub mod q10;
 pub mod q11;
@@ -28,5 +32,6 @@ pub mod q7;
 pub mod q8;
 pub mod q9;
 mod tokenizer;
+pub mod union_find;
 mod vec2;
 pub mod q22;
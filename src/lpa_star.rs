@@ -0,0 +1,396 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::a_star_search::AStarSearch;
+use crate::cost::Cost;
+use crate::graph::{Graph, Weight};
+use crate::grid::{Grid, Tile};
+
+type Key<C> = (C, C);
+
+fn cmp_key<C: Cost>(a: &Key<C>, b: &Key<C>) -> Ordering {
+    a.0.cmp_cost(&b.0).then_with(|| a.1.cmp_cost(&b.1))
+}
+
+// The edges into a node, which the rhs values are computed from. `Index` is whatever has to
+// be built once to find them quickly
+pub trait Predecessors: AStarSearch {
+    type Index;
+
+    fn predecessor_index(&self) -> Self::Index;
+    fn weighted_predecessors(
+        &self,
+        index: &Self::Index,
+        node: &Self::Node,
+    ) -> Vec<(Self::Node, Self::Cost)>;
+}
+
+// Moving onto a traversable cell costs the same from any side
+impl<K> Predecessors for Grid<K>
+where
+    K: Clone + Tile + Eq + Hash,
+{
+    type Index = ();
+
+    fn predecessor_index(&self) {}
+
+    fn weighted_predecessors(&self, _: &(), node: &Self::Node) -> Vec<(Self::Node, K::Cost)> {
+        let Some(tile) = self.get(*node).filter(|tile| tile.traversable()) else {
+            return Vec::new();
+        };
+
+        self.deltas(*node, false)
+            .into_iter()
+            .map(|delta| {
+                (
+                    (node.0 as i32 + delta.0) as usize,
+                    (node.1 as i32 + delta.1) as usize,
+                )
+            })
+            .filter_map(|u| Some((u, tile.cost_from(self.get(u)?.clone()))))
+            .collect()
+    }
+}
+
+impl<K, W> Predecessors for Graph<K, W>
+where
+    K: Eq + Hash + Clone,
+    W: Weight + Debug + Clone,
+{
+    type Index = HashMap<K, Vec<(K, W::Cost)>>;
+
+    fn predecessor_index(&self) -> Self::Index {
+        self.reverse_adjacency()
+    }
+
+    fn weighted_predecessors(&self, index: &Self::Index, node: &K) -> Vec<(K, W::Cost)> {
+        index.get(node).cloned().unwrap_or_default()
+    }
+}
+
+// Lifelong Planning A*: keeps the g and rhs values of the last search around so that when
+// obstacles appear only the part of the search they invalidate is repaired. Nodes are
+// blocked on top of the underlying graph, which is never modified. Edges may be directed,
+// rhs comes from the edges into a node and changes to g are passed along the edges out of it
+pub struct LpaStar<'a, G: Predecessors> {
+    graph: &'a G,
+    predecessor_index: G::Index,
+    start: G::Node,
+    goal: G::Node,
+    heuristic: fn(G::Node, G::Node) -> G::Cost,
+    g: HashMap<G::Node, G::Cost>,
+    rhs: HashMap<G::Node, G::Cost>,
+    // The key each queued node was last pushed with, heap entries with another key are stale
+    queued: HashMap<G::Node, Key<G::Cost>>,
+    open_set: BinaryHeap<Elem<G::Node, G::Cost>>,
+    blocked: HashSet<G::Node>,
+    expanded: usize,
+}
+
+struct Elem<N, C>(Key<C>, N);
+impl<N, C: Cost> PartialEq for Elem<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<N, C: Cost> Eq for Elem<N, C> {}
+impl<N, C: Cost> Ord for Elem<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_key(&self.0, &other.0).reverse()
+    }
+}
+impl<N, C: Cost> PartialOrd for Elem<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, G: Predecessors> LpaStar<'a, G> {
+    pub fn new(
+        graph: &'a G,
+        start: G::Node,
+        goal: G::Node,
+        heuristic: fn(G::Node, G::Node) -> G::Cost,
+    ) -> Self {
+        let mut search = Self {
+            graph,
+            predecessor_index: graph.predecessor_index(),
+            start: start.clone(),
+            goal,
+            heuristic,
+            g: HashMap::new(),
+            rhs: HashMap::new(),
+            queued: HashMap::new(),
+            open_set: BinaryHeap::new(),
+            blocked: HashSet::new(),
+            expanded: 0,
+        };
+
+        search.rhs.insert(start.clone(), G::Cost::ZERO);
+        search.push(start);
+        search.compute_shortest_path();
+        search
+    }
+
+    // Nodes popped from the queue over every search so far
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    pub fn shortest_path_length(&self) -> Option<G::Cost> {
+        Some(self.g_of(&self.goal)).filter(|g| *g != G::Cost::INFINITY)
+    }
+
+    // Blocks `node` and repairs the search, returns the new shortest path length
+    pub fn add_obstacle(&mut self, node: G::Node) -> Option<G::Cost> {
+        if !self.blocked.contains(&node) {
+            let neighbours = self.neighbours(&node);
+            self.blocked.insert(node.clone());
+
+            self.update_node(node);
+            for (u, _) in neighbours {
+                self.update_node(u);
+            }
+
+            self.compute_shortest_path();
+        }
+
+        self.shortest_path_length()
+    }
+
+    // Walks back from the goal through predecessors whose g value accounts exactly for the
+    // current one. Nodes still queued may hold stale g values, so only consistent ones are used
+    pub fn path(&self) -> Option<Vec<G::Node>> {
+        self.shortest_path_length()?;
+
+        let mut current = self.goal.clone();
+        let mut path = vec![current.clone()];
+
+        while current != self.start {
+            let g = self.g_of(&current);
+            current = self
+                .predecessors(&current)
+                .into_iter()
+                .find(|(u, w)| {
+                    let g_u = self.g_of(u);
+                    g_u != G::Cost::INFINITY && g_u == self.rhs_of(u) && g_u + *w == g
+                })?
+                .0;
+            path.push(current.clone());
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    fn g_of(&self, node: &G::Node) -> G::Cost {
+        *self.g.get(node).unwrap_or(&G::Cost::INFINITY)
+    }
+
+    fn rhs_of(&self, node: &G::Node) -> G::Cost {
+        *self.rhs.get(node).unwrap_or(&G::Cost::INFINITY)
+    }
+
+    fn neighbours(&self, node: &G::Node) -> Vec<(G::Node, G::Cost)> {
+        if self.blocked.contains(node) {
+            return Vec::new();
+        }
+
+        self.graph
+            .weighted_neighbours(node)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(u, _)| !self.blocked.contains(u))
+            .collect()
+    }
+
+    fn predecessors(&self, node: &G::Node) -> Vec<(G::Node, G::Cost)> {
+        if self.blocked.contains(node) {
+            return Vec::new();
+        }
+
+        self.graph
+            .weighted_predecessors(&self.predecessor_index, node)
+            .into_iter()
+            .filter(|(u, _)| !self.blocked.contains(u))
+            .collect()
+    }
+
+    fn key(&self, node: &G::Node) -> Key<G::Cost> {
+        let (g, rhs) = (self.g_of(node), self.rhs_of(node));
+        let k = if g.cmp_cost(&rhs) == Ordering::Less {
+            g
+        } else {
+            rhs
+        };
+
+        if k == G::Cost::INFINITY {
+            (k, k)
+        } else {
+            (k + (self.heuristic)(node.clone(), self.goal.clone()), k)
+        }
+    }
+
+    fn push(&mut self, node: G::Node) {
+        let key = self.key(&node);
+        self.queued.insert(node.clone(), key);
+        self.open_set.push(Elem(key, node));
+    }
+
+    fn top_key(&mut self) -> Option<Key<G::Cost>> {
+        while let Some(Elem(key, node)) = self.open_set.peek() {
+            if self.queued.get(node) == Some(key) {
+                return Some(*key);
+            }
+            self.open_set.pop();
+        }
+
+        None
+    }
+
+    fn update_node(&mut self, node: G::Node) {
+        if node != self.start {
+            let rhs = self
+                .predecessors(&node)
+                .into_iter()
+                .map(|(u, w)| (self.g_of(&u), w))
+                .filter(|(g, _)| *g != G::Cost::INFINITY)
+                .map(|(g, w)| g + w)
+                .min_by(|a, b| a.cmp_cost(b))
+                .unwrap_or(G::Cost::INFINITY);
+            self.rhs.insert(node.clone(), rhs);
+        }
+
+        self.queued.remove(&node);
+        if self.g_of(&node) != self.rhs_of(&node) {
+            self.push(node);
+        }
+    }
+
+    fn compute_shortest_path(&mut self) {
+        while let Some(top) = self.top_key() {
+            let goal_key = self.key(&self.goal);
+            let goal_consistent = self.g_of(&self.goal) == self.rhs_of(&self.goal);
+            if cmp_key(&top, &goal_key) != Ordering::Less && goal_consistent {
+                break;
+            }
+
+            let Elem(_, node) = self.open_set.pop().unwrap();
+            self.queued.remove(&node);
+            self.expanded += 1;
+
+            let (g, rhs) = (self.g_of(&node), self.rhs_of(&node));
+            if g.cmp_cost(&rhs) == Ordering::Greater {
+                self.g.insert(node.clone(), rhs);
+            } else {
+                self.g.insert(node.clone(), G::Cost::INFINITY);
+                self.update_node(node.clone());
+            }
+
+            for (u, _) in self.neighbours(&node) {
+                self.update_node(u);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Floor(bool);
+
+    impl Tile for Floor {
+        type Cost = usize;
+
+        fn traversable(&self) -> bool {
+            self.0
+        }
+
+        fn cost_from<V>(&self, _other: V) -> usize
+        where
+            V: Tile,
+        {
+            1
+        }
+    }
+
+    fn manhattan(a: (usize, usize), b: (usize, usize)) -> usize {
+        a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+    }
+
+    #[test]
+    fn test_lpa_star_matches_a_star_after_every_obstacle() {
+        let size = 12;
+        let grid = Grid::with_capacity_and_default(size, size, Floor(true));
+        let (start, goal) = ((0, 0), (size - 1, size - 1));
+
+        let mut lpa = LpaStar::new(&grid, start, goal, manhattan);
+        assert_eq!(lpa.shortest_path_length(), Some(2 * (size - 1)));
+
+        // The same obstacles are placed on a copy of the grid to compare with a fresh A*
+        let mut blocked = grid.clone();
+        let mut state = 7u64;
+        for _ in 0..size * size {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let cell = ((state >> 33) as usize % size, (state >> 45) as usize % size);
+            if cell == start || cell == goal {
+                continue;
+            }
+
+            blocked[cell] = Floor(false);
+            let length = lpa.add_obstacle(cell);
+
+            let expected = blocked.a_star_search_result(start, goal, manhattan);
+            assert_eq!(length, expected.map(|result| result.cost));
+
+            if let Some(path) = lpa.path() {
+                assert_eq!(path.len(), length.unwrap() + 1);
+                assert!(path.iter().all(|cell| blocked[*cell].traversable()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lpa_star_repairs_less_than_a_fresh_search() {
+        let grid = Grid::with_capacity_and_default(30, 30, Floor(true));
+        let mut lpa = LpaStar::new(&grid, (0, 0), (29, 29), manhattan);
+        let initial = lpa.expanded();
+
+        // A wall across the bottom right corner only forces a local detour
+        lpa.add_obstacle((28, 29));
+        assert_eq!(lpa.shortest_path_length(), Some(58));
+        assert!(lpa.expanded() - initial < initial);
+    }
+
+    #[test]
+    fn test_lpa_star_directed_graph() {
+        // t has no edges out, and going back from b to s costs more than going forward
+        let mut graph = Graph::new();
+        for (u, v, w) in [
+            ("s", "a", 1u32),
+            ("a", "t", 1),
+            ("s", "b", 1),
+            ("b", "s", 10),
+            ("b", "t", 5),
+        ] {
+            graph.add_edge(u, v, w);
+        }
+
+        let mut lpa = LpaStar::new(&graph, "s", "t", |_, _| 0);
+        assert_eq!(lpa.shortest_path_length(), Some(2));
+        assert_eq!(lpa.path(), Some(vec!["s", "a", "t"]));
+
+        assert_eq!(lpa.add_obstacle("a"), Some(6));
+        assert_eq!(lpa.path(), Some(vec!["s", "b", "t"]));
+
+        assert_eq!(lpa.add_obstacle("b"), None);
+    }
+}
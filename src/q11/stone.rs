@@ -16,7 +16,7 @@ impl Stone {
             0 => (Stone(1), None),
             n => {
                 let digit_count = n.ilog10() as usize + 1;
-                if digit_count % 2 == 0 {
+                if digit_count.is_multiple_of(2) {
                     let half = digit_count / 2;
                     let divisor = 10_usize.pow(half as u32);
 
@@ -1,11 +1,13 @@
-use core::f64;
 use std::fmt::Display;
 
-use hashbrown::HashMap;
+use hashbrown::HashSet;
 
+pub use crate::a_star_search::SearchResult;
 use crate::{
-    graph::{Graph, Weight},
-    grid::Grid,
+    a_star_search::AStarSearch,
+    graph::{Corridor, Graph, GridGraphBuilder, Weight},
+    grid::{self, Grid},
+    implicit_search::StateSpace,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,6 +26,22 @@ impl Display for Tile {
     }
 }
 
+// Every step costs the same, turns are added on top by the reindeer graph
+impl grid::Tile for Tile {
+    type Cost = u64;
+
+    fn traversable(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    fn cost_from<V>(&self, _other: V) -> u64
+    where
+        V: grid::Tile,
+    {
+        1
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Dir {
     East,
@@ -90,10 +108,12 @@ pub enum Move {
 }
 
 impl Weight for Move {
-    fn weight(&self) -> f64 {
+    type Cost = u64;
+
+    fn weight(&self) -> u64 {
         match self {
-            Self::Forward => 1.0,
-            Self::Clockwise | Self::AntiClockwise => 1000.0,
+            Self::Forward => 1,
+            Self::Clockwise | Self::AntiClockwise => 1000,
         }
     }
 }
@@ -128,135 +148,233 @@ impl Display for Vertex {
     }
 }
 
-pub fn get_shortest_path_cost(input: &str) -> f64 {
+pub fn get_shortest_path_cost(input: &str) -> u64 {
     let (grid, start, goal) = parse_input(input);
-    let graph = build_graph(&grid, start);
-    let (dist, _) = graph.dijkstra(Vertex {
+
+    // The (position, direction) states are searched directly, without building the graph
+    let space = StateSpace::new(|vertex: &Vertex| {
+        successors(&grid, vertex)
+            .into_iter()
+            .map(|(next, r#move)| (next, r#move.weight()))
+            .collect()
+    });
+
+    let start = Vertex {
         position: start,
         direction: Dir::East,
-    });
+    };
 
-    let mut min_cost = f64::INFINITY;
-    for (vert, cost) in dist {
-        if vert.position == goal {
-            min_cost = min_cost.min(cost);
-        }
-    }
-    min_cost
+    space
+        .dijkstra(start, |vertex| vertex.position == goal)
+        .map_or(u64::MAX, |result| result.cost)
+}
+
+// The graph with the start vertex and every facing at the goal
+fn build_search(input: &str) -> (Graph<Vertex, Corridor<u64>>, Vertex, Vec<Vertex>) {
+    let (grid, start, goal) = parse_input(input);
+
+    let graph = build_graph(&grid, start, goal);
+
+    let start = Vertex {
+        position: start,
+        direction: Dir::East,
+    };
+    let goals = [Dir::North, Dir::East, Dir::South, Dir::West]
+        .into_iter()
+        .map(|direction| Vertex {
+            position: goal,
+            direction,
+        })
+        .filter(|vertex| graph.contains(vertex))
+        .collect::<Vec<_>>();
+
+    (graph, start, goals)
+}
+
+// Searches the built graph from the start and from every facing at the goal at once
+pub fn get_shortest_path_cost_bidirectional(input: &str) -> u64 {
+    let (graph, start, goals) = build_search(input);
+
+    graph
+        .bidirectional_dijkstra_any(start, &goals)
+        .map_or(u64::MAX, |result| result.cost)
+}
+
+// The bidirectional search and a one sided search over the same graph, to compare the
+// vertices each expands
+pub fn compare_shortest_path_searches(
+    input: &str,
+) -> Option<(SearchResult<Vertex, u64>, SearchResult<Vertex, u64>)> {
+    let (graph, start, goals) = build_search(input);
+
+    let two_sided = graph.bidirectional_dijkstra_any(start, &goals)?;
+    let one_sided =
+        graph.a_star_search_where(start, |vertex| goals.contains(vertex), |_| 0, false)?;
+
+    Some((two_sided, one_sided))
 }
 
 pub fn get_area_covered_by_shortest_paths(input: &str) -> usize {
     let (grid, start, goal) = parse_input(input);
 
-    let graph = build_graph(&grid, start);
+    let graph = build_graph(&grid, start, goal);
 
     let (dist, prev) = graph.dijkstra(Vertex {
         position: start,
         direction: Dir::East,
     });
 
-    let mut min_cost = f64::INFINITY;
+    let mut min_cost = u64::MAX;
     let mut min_cost_vertex = Vertex {
         position: (0, 0),
         direction: Dir::East,
     };
-    for (vert, cost) in dist {
-        if vert.position == goal && cost < min_cost {
-            min_cost = cost;
-            min_cost_vertex = vert;
+    for (vert, cost) in &dist {
+        if vert.position == goal && *cost < min_cost {
+            min_cost = *cost;
+            min_cost_vertex = *vert;
         }
     }
-    let graph = prev.shortest_paths_subgraph(
+    let paths = prev.shortest_paths_subgraph(
         min_cost_vertex,
         Vertex {
             position: start,
             direction: Dir::East,
         },
     );
-    let collapsed_graph = collapse_graph(&graph);
 
-    collapsed_graph.len()
-}
-
-fn collapse_graph<W: Weight>(graph: &Graph<Vertex, W>) -> Graph<(usize, usize), f64> {
-    let mut new_graph = Graph::new();
-
-    for (from, edges) in &graph.edges {
-        for (to, weight) in edges {
-            let new_from = from.position;
-            let new_to = to.position;
-            new_graph.add_edge(new_from, new_to, weight.weight());
+    // Edges of the subgraph point back to a predecessor and carry the distance of their
+    // source, the corridors they stand for are the ones that account for it exactly
+    let mut tiles = HashSet::new();
+    for (to, edges) in &paths.edges {
+        tiles.insert(to.position);
+
+        for (from, cost) in edges {
+            tiles.insert(from.position);
+            tiles.extend(
+                graph.edges[from]
+                    .iter()
+                    .filter(|(v, corridor)| v == to && dist[from] + corridor.cost == *cost)
+                    .flat_map(|(_, corridor)| corridor.cells.iter().copied()),
+            );
         }
     }
 
-    new_graph
+    tiles.len()
 }
 
-fn build_graph(grid: &Grid<Tile>, start: (usize, usize)) -> Graph<Vertex, Move> {
-    // Worst case capacity is 3 moves per tile, the two rotations and the forward moves
-    let mut graph = Graph::with_capacity(grid.rows * grid.cols * 3);
+fn successors(grid: &Grid<Tile>, curr: &Vertex) -> Vec<(Vertex, Move)> {
+    let mut successors = Vec::with_capacity(3);
 
-    let mut queue = Vec::from([Vertex {
-        position: start,
-        direction: Dir::East,
-    }]);
+    for next_dir in curr.direction.get_90_rotations() {
+        let r#move = if next_dir == curr.direction.get_clockwise_rotation() {
+            Move::Clockwise
+        } else if next_dir == curr.direction.get_anti_clockwise_rotation() {
+            Move::AntiClockwise
+        } else {
+            panic!("Invalid rotation");
+        };
+        let new_vertex = Vertex {
+            position: curr.position,
+            direction: next_dir,
+        };
 
-    let mut seen: HashMap<Vertex, bool> = HashMap::new();
+        successors.push((new_vertex, r#move));
+    }
 
-    while let Some(curr) = queue.pop() {
-        if seen.contains_key(&curr) {
-            continue;
-        }
+    let (dx, dy) = curr.direction.into();
 
-        seen.insert(curr, true);
+    let next_coord = (curr.position.0 as i32 + dx, curr.position.1 as i32 + dy);
 
-        for next_dir in curr.direction.get_90_rotations() {
-            let r#move = if next_dir == curr.direction.get_clockwise_rotation() {
-                Move::Clockwise
-            } else if next_dir == curr.direction.get_anti_clockwise_rotation() {
-                Move::AntiClockwise
-            } else {
-                panic!("Invalid rotation");
-            };
-            let new_vertex = Vertex {
-                position: curr.position,
-                direction: next_dir,
-            };
+    if next_coord.0 < 0 || next_coord.1 < 0 {
+        return successors;
+    }
 
-            if !seen.contains_key(&new_vertex) {
-                queue.push(new_vertex);
-            }
-            graph.add_edge(curr, new_vertex, r#move);
-        }
+    if next_coord.0 >= grid.rows as i32 || next_coord.1 >= grid.cols as i32 {
+        return successors;
+    }
 
-        let (dx, dy) = curr.direction.into();
+    let next_coord = (next_coord.0 as usize, next_coord.1 as usize);
 
-        let next_coord = (curr.position.0 as i32 + dx, curr.position.1 as i32 + dy);
+    if grid[next_coord] == Tile::Obstacle {
+        return successors;
+    }
 
-        if next_coord.0 < 0 || next_coord.1 < 0 {
-            continue;
-        }
+    let next_vertex = Vertex {
+        position: next_coord,
+        direction: curr.direction,
+    };
 
-        if next_coord.0 >= grid.rows as i32 || next_coord.1 >= grid.cols as i32 {
-            continue;
-        }
+    successors.push((next_vertex, Move::Forward));
 
-        let next_coord = (next_coord.0 as usize, next_coord.1 as usize);
+    successors
+}
 
-        if grid[next_coord] == Tile::Obstacle {
-            continue;
-        }
+fn direction(from: (usize, usize), to: (usize, usize)) -> Dir {
+    (to.0 as i32 - from.0 as i32, to.1 as i32 - from.1 as i32).into()
+}
 
-        let r#move = Move::Forward;
-        let next_vertex = Vertex {
-            position: next_coord,
-            direction: curr.direction,
-        };
+// The (position, direction) states at the junctions of the maze, with corridors contracted
+// by the grid builder. Following a corridor costs its steps plus a quarter turn at each bend,
+// turning on the spot only pays off at junctions
+fn build_graph(
+    grid: &Grid<Tile>,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Graph<Vertex, Corridor<u64>> {
+    let corridors = GridGraphBuilder::new(grid)
+        .contract_corridors(true)
+        .keep(start)
+        .keep(goal)
+        .build();
+    let mut graph = Graph::with_capacity(corridors.len() * 4);
+
+    for junction in &corridors.vertices {
+        for heading in [Dir::North, Dir::East, Dir::South, Dir::West] {
+            let vertex = Vertex {
+                position: *junction,
+                direction: heading,
+            };
 
-        if !seen.contains_key(&next_vertex) {
-            queue.push(next_vertex);
+            for (next, r#move) in successors(grid, &vertex) {
+                if next.position == *junction {
+                    let turn = Corridor {
+                        cost: r#move.weight(),
+                        cells: Vec::new(),
+                    };
+                    graph.add_edge(vertex, next, turn);
+                }
+            }
+        }
+
+        for (end, corridor) in corridors.edges.get(junction).into_iter().flatten() {
+            let mut cells = vec![*junction];
+            cells.extend(&corridor.cells);
+            cells.push(*end);
+
+            let headings = cells
+                .windows(2)
+                .map(|pair| direction(pair[0], pair[1]))
+                .collect::<Vec<_>>();
+            let bends = headings
+                .windows(2)
+                .filter(|pair| pair[0] != pair[1])
+                .count() as u64;
+
+            let from = Vertex {
+                position: *junction,
+                direction: headings[0],
+            };
+            let to = Vertex {
+                position: *end,
+                direction: headings[headings.len() - 1],
+            };
+            let corridor = Corridor {
+                cost: corridor.cost + bends * Move::Clockwise.weight(),
+                cells: corridor.cells.clone(),
+            };
+            graph.add_edge(from, to, corridor);
         }
-        graph.add_edge(curr, next_vertex, r#move);
     }
 
     graph
@@ -341,12 +459,23 @@ mod tests {
 ";
 
     #[rstest]
-    #[case(TEST_INPUT, 7036.0)]
-    #[case(OTHER_TEST_INPUT, 11048.0)]
-    fn test_get_shortest_path_cost(#[case] input: &str, #[case] expected: f64) {
+    #[case(TEST_INPUT, 7036)]
+    #[case(OTHER_TEST_INPUT, 11048)]
+    fn test_get_shortest_path_cost(#[case] input: &str, #[case] expected: u64) {
         assert_eq!(get_shortest_path_cost(input), expected);
     }
 
+    #[rstest]
+    #[case(TEST_INPUT, 7036)]
+    #[case(OTHER_TEST_INPUT, 11048)]
+    fn test_get_shortest_path_cost_bidirectional(#[case] input: &str, #[case] expected: u64) {
+        assert_eq!(get_shortest_path_cost_bidirectional(input), expected);
+
+        let (two_sided, one_sided) = compare_shortest_path_searches(input).unwrap();
+        assert_eq!((two_sided.cost, one_sided.cost), (expected, expected));
+        assert!(two_sided.expanded < one_sided.expanded);
+    }
+
     #[rstest]
     #[case(TEST_INPUT, 45)]
     #[case(OTHER_TEST_INPUT, 64)]
@@ -0,0 +1,204 @@
+use std::fmt::{Display, Write};
+
+use super::op_code::Instruction;
+
+// One instruction per line, `mnemonic operand`, optionally prefixed by its `address:` and
+// followed by a `;` comment. Combo operands are written as `0` to `3`, `a`, `b` or `c`,
+// literal operands as plain numbers. An instruction without an operand is only accepted at
+// the end of a program, so odd length programs round trip even though they cannot be run
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssemblyError {
+    UnknownInstruction { line: usize, value: String },
+    InvalidOperand { line: usize, value: String },
+    MissingOperand { line: usize },
+    TooManyOperands { line: usize },
+}
+
+impl Display for AssemblyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownInstruction { line, value } => {
+                write!(f, "line {}: unknown instruction '{}'", line, value)
+            }
+            Self::InvalidOperand { line, value } => {
+                write!(f, "line {}: invalid operand '{}'", line, value)
+            }
+            Self::MissingOperand { line } => write!(f, "line {}: missing operand", line),
+            Self::TooManyOperands { line } => write!(f, "line {}: too many operands", line),
+        }
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+fn render_operand(instruction: Instruction, operand: usize) -> String {
+    match operand {
+        4 if instruction.has_combo_operand() => "a".to_string(),
+        5 if instruction.has_combo_operand() => "b".to_string(),
+        6 if instruction.has_combo_operand() => "c".to_string(),
+        _ => operand.to_string(),
+    }
+}
+
+fn parse_operand(instruction: Instruction, operand: &str) -> Option<usize> {
+    match operand {
+        "a" if instruction.has_combo_operand() => Some(4),
+        "b" if instruction.has_combo_operand() => Some(5),
+        "c" if instruction.has_combo_operand() => Some(6),
+        _ => operand.parse().ok().filter(|value| *value < 8),
+    }
+}
+
+// Op codes outside the instruction set are rendered but cannot be assembled again
+pub(super) fn render_instruction(op_code: usize, operand: Option<usize>) -> String {
+    let Some(instruction) = Instruction::from_op_code(op_code) else {
+        return format!("invalid op code {}", op_code);
+    };
+
+    match operand {
+        Some(operand) => format!(
+            "{} {}",
+            instruction.mnemonic(),
+            render_operand(instruction, operand)
+        ),
+        None => instruction.mnemonic().to_string(),
+    }
+}
+
+pub fn disassemble(program: &[usize]) -> String {
+    let mut output = String::new();
+
+    for (address, chunk) in program.chunks(2).enumerate() {
+        let rendered = render_instruction(chunk[0], chunk.get(1).copied());
+        writeln!(output, "{:02}: {}", address * 2, rendered).unwrap();
+    }
+
+    output
+}
+
+pub fn assemble(source: &str) -> Result<Vec<usize>, AssemblyError> {
+    let mut program = Vec::new();
+    // A line without an operand has to be the last instruction
+    let mut missing_operand = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+
+        let line = line.split(';').next().unwrap();
+        let line = match line.split_once(':') {
+            Some((address, rest)) if address.trim().parse::<usize>().is_ok() => rest,
+            _ => line,
+        };
+
+        let mut fields = line.split_whitespace();
+        let Some(mnemonic) = fields.next() else {
+            continue;
+        };
+
+        if let Some(line) = missing_operand {
+            return Err(AssemblyError::MissingOperand { line });
+        }
+
+        let instruction = Instruction::from_mnemonic(mnemonic).ok_or_else(|| {
+            AssemblyError::UnknownInstruction {
+                line: line_number,
+                value: mnemonic.to_string(),
+            }
+        })?;
+        program.push(instruction.op_code());
+
+        match fields.next() {
+            Some(operand) => {
+                let value = parse_operand(instruction, operand).ok_or_else(|| {
+                    AssemblyError::InvalidOperand {
+                        line: line_number,
+                        value: operand.to_string(),
+                    }
+                })?;
+                program.push(value);
+            }
+            None => missing_operand = Some(line_number),
+        }
+
+        if fields.next().is_some() {
+            return Err(AssemblyError::TooManyOperands { line: line_number });
+        }
+    }
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: [usize; 16] = [2, 4, 1, 1, 7, 5, 1, 5, 4, 2, 5, 5, 0, 3, 3, 0];
+
+    #[test]
+    fn test_disassemble() {
+        let expected = "\
+00: bst a
+02: bxl 1
+04: cdv b
+06: bxl 5
+08: bxc 2
+10: out b
+12: adv 3
+14: jnz 0
+";
+        assert_eq!(disassemble(&PROGRAM), expected);
+    }
+
+    #[test]
+    fn test_assemble_round_trips() {
+        assert_eq!(assemble(&disassemble(&PROGRAM)), Ok(PROGRAM.to_vec()));
+
+        let odd = [0, 1, 5, 4, 3];
+        assert_eq!(assemble(&disassemble(&odd)), Ok(odd.to_vec()));
+    }
+
+    #[test]
+    fn test_assemble_comments_and_addresses() {
+        let source = "
+; divide a by 8 until it reaches zero
+adv 3     ; a = a >> 3
+out a
+04: jnz 0
+";
+        assert_eq!(assemble(source), Ok(vec![0, 3, 5, 4, 3, 0]));
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(
+            assemble("adv 3\nmul 2"),
+            Err(AssemblyError::UnknownInstruction {
+                line: 2,
+                value: "mul".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("bxl a"),
+            Err(AssemblyError::InvalidOperand {
+                line: 1,
+                value: "a".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("out 8"),
+            Err(AssemblyError::InvalidOperand {
+                line: 1,
+                value: "8".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("adv\nout a"),
+            Err(AssemblyError::MissingOperand { line: 1 })
+        );
+        assert_eq!(
+            assemble("out a b"),
+            Err(AssemblyError::TooManyOperands { line: 1 })
+        );
+    }
+}
@@ -1,91 +1,301 @@
+use std::fmt::Display;
+
 use super::op_code::Instruction;
+use super::word::Word;
+
+// Enough for any program that halts on a sensibly sized register a, a program stuck in a
+// loop is stopped instead of running forever
+pub const DEFAULT_STEP_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputerError {
+    InvalidOpCode { address: usize, value: usize },
+    // Combo operand 7 is reserved and never appears in valid programs
+    ReservedOperand { address: usize },
+    InvalidOperand { address: usize, value: usize },
+    ShiftOverflow { address: usize, shift: usize },
+    OddLengthProgram { length: usize },
+    StepLimitExceeded { limit: usize },
+}
+
+impl Display for ComputerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOpCode { address, value } => {
+                write!(f, "address {}: invalid op code {}", address, value)
+            }
+            Self::ReservedOperand { address } => {
+                write!(f, "address {}: reserved combo operand 7", address)
+            }
+            Self::InvalidOperand { address, value } => {
+                write!(f, "address {}: invalid operand {}", address, value)
+            }
+            Self::ShiftOverflow { address, shift } => {
+                write!(f, "address {}: division by 2^{} overflows", address, shift)
+            }
+            Self::OddLengthProgram { length } => {
+                write!(f, "program has an odd length of {}", length)
+            }
+            Self::StepLimitExceeded { limit } => {
+                write!(f, "program did not halt within {} steps", limit)
+            }
+        }
+    }
+}
 
+impl std::error::Error for ComputerError {}
+
+// Registers are usize unless a wider word is asked for, the program and its output are
+// always made of 3 bit values
 #[derive(Debug, Clone, Copy)]
-pub struct Computer {
-    a: usize,
-    b: usize,
-    c: usize,
+pub struct Computer<R = usize> {
+    a: R,
+    b: R,
+    c: R,
+    step_limit: usize,
 }
 
-impl Computer {
+impl<R: Word> Default for Computer<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Word> Computer<R> {
     pub fn new() -> Self {
-        Self { a: 0, b: 0, c: 0 }
+        Self::new_with_registers(R::from_usize(0), R::from_usize(0), R::from_usize(0))
     }
-    pub fn run(&mut self, instructions: &[usize]) -> Vec<usize> {
-        use Instruction::*;
+
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    pub fn step_limit(&self) -> usize {
+        self.step_limit
+    }
+
+    pub fn run(&mut self, instructions: &[usize]) -> Result<Vec<usize>, ComputerError> {
+        if !instructions.len().is_multiple_of(2) {
+            return Err(ComputerError::OddLengthProgram {
+                length: instructions.len(),
+            });
+        }
+
         let mut out = Vec::with_capacity(17);
         let mut instruction_pointer = 0;
+        let mut steps = 0;
 
-        while instruction_pointer < instructions.len() - 1 {
-            let instruction = instructions[instruction_pointer].into();
-            let operand = instructions[instruction_pointer + 1];
-            match instruction {
-                Adv => {
-                    let combo = self.evaluate_combo_operand(operand);
-                    let denominator = 1 << combo;
-                    self.a /= denominator;
-                }
-                Bxl => {
-                    self.b ^= operand;
-                }
-                Bst => {
-                    let combo = self.evaluate_combo_operand(operand);
-                    self.b = combo & 0x7;
-                }
-                Jnz if self.a != 0 => {
-                    instruction_pointer = operand;
-                    continue;
-                }
-                Jnz => {}
-                Bxc => {
-                    self.b ^= self.c;
-                }
-                Out => {
-                    let combo = self.evaluate_combo_operand(operand);
-                    out.push(combo & 0x7);
-                }
-                Bdv => {
-                    let combo = self.evaluate_combo_operand(operand);
-                    let denominator = 1 << combo;
-                    self.b = self.a / denominator;
-                }
-                Cdv => {
-                    let combo = self.evaluate_combo_operand(operand);
-                    let denominator = 1 << combo;
-                    self.c = self.a / denominator;
-                }
+        while instruction_pointer + 1 < instructions.len() {
+            if steps == self.step_limit {
+                return Err(ComputerError::StepLimitExceeded {
+                    limit: self.step_limit,
+                });
             }
 
-            instruction_pointer += 2;
+            let (next, output) = self.step(instructions, instruction_pointer)?;
+            out.extend(output);
+            instruction_pointer = next;
+            steps += 1;
         }
 
-        out
+        Ok(out)
     }
 
+    // Executes the instruction at `instruction_pointer`, returns where execution continues
+    // and the value it output, if any. The caller checks the program has not halted
     #[inline(always)]
-    pub fn evaluate_combo_operand(&self, operand: usize) -> usize {
+    pub fn step(
+        &mut self,
+        instructions: &[usize],
+        instruction_pointer: usize,
+    ) -> Result<(usize, Option<usize>), ComputerError> {
+        use Instruction::*;
+        let value = instructions[instruction_pointer];
+        let instruction = Instruction::from_op_code(value).ok_or(ComputerError::InvalidOpCode {
+            address: instruction_pointer,
+            value,
+        })?;
+        let operand = instructions[instruction_pointer + 1];
+
+        // Dividing by a power of two is a right shift, which has to fit in the register
+        let divide = |numerator: &R, combo: R| {
+            numerator
+                .shift_right(&combo)
+                .ok_or(ComputerError::ShiftOverflow {
+                    address: instruction_pointer,
+                    shift: combo.to_usize().unwrap_or(usize::MAX),
+                })
+        };
+
+        match instruction {
+            Adv => {
+                let combo = self.evaluate_combo_operand(operand, instruction_pointer)?;
+                self.a = divide(&self.a, combo)?;
+            }
+            Bxl => {
+                self.b = self.b.xor(&R::from_usize(operand));
+            }
+            Bst => {
+                let combo = self.evaluate_combo_operand(operand, instruction_pointer)?;
+                self.b = R::from_usize(combo.low_bits(0x7));
+            }
+            Jnz if !self.a.is_zero() => {
+                return Ok((operand, None));
+            }
+            Jnz => {}
+            Bxc => {
+                self.b = self.b.xor(&self.c);
+            }
+            Out => {
+                let combo = self.evaluate_combo_operand(operand, instruction_pointer)?;
+                return Ok((instruction_pointer + 2, Some(combo.low_bits(0x7))));
+            }
+            Bdv => {
+                let combo = self.evaluate_combo_operand(operand, instruction_pointer)?;
+                self.b = divide(&self.a, combo)?;
+            }
+            Cdv => {
+                let combo = self.evaluate_combo_operand(operand, instruction_pointer)?;
+                self.c = divide(&self.a, combo)?;
+            }
+        }
+
+        Ok((instruction_pointer + 2, None))
+    }
+
+    #[inline(always)]
+    pub fn evaluate_combo_operand(
+        &self,
+        operand: usize,
+        address: usize,
+    ) -> Result<R, ComputerError> {
         match operand {
-            0..=3 => operand,
-            4 => self.a,
-            5 => self.b,
-            6 => self.c,
-            _ => panic!("Invalid Operand"),
+            0..=3 => Ok(R::from_usize(operand)),
+            4 => Ok(self.a.clone()),
+            5 => Ok(self.b.clone()),
+            6 => Ok(self.c.clone()),
+            7 => Err(ComputerError::ReservedOperand { address }),
+            _ => Err(ComputerError::InvalidOperand {
+                address,
+                value: operand,
+            }),
         }
     }
 
-    pub fn set_register_a(&mut self, value: usize) {
+    pub fn set_register_a(&mut self, value: R) {
         self.a = value;
     }
 
-    pub fn set_register_b(&mut self, value: usize) {
+    pub fn set_register_b(&mut self, value: R) {
         self.b = value;
     }
 
-    pub fn set_register_c(&mut self, value: usize) {
+    pub fn set_register_c(&mut self, value: R) {
         self.c = value;
     }
 
-    pub fn new_with_registers(a: usize, b: usize, c: usize) -> Self {
-        Self { a, b, c }
+    pub fn registers(&self) -> (R, R, R) {
+        (self.a.clone(), self.b.clone(), self.c.clone())
+    }
+
+    pub fn new_with_registers(a: R, b: R, c: R) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            step_limit: DEFAULT_STEP_LIMIT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_errors() {
+        let mut computer: Computer = Computer::new_with_registers(10, 0, 0);
+
+        assert_eq!(
+            computer.run(&[5, 4, 8, 0]),
+            Err(ComputerError::InvalidOpCode {
+                address: 2,
+                value: 8
+            })
+        );
+        assert_eq!(
+            computer.run(&[5, 7]),
+            Err(ComputerError::ReservedOperand { address: 0 })
+        );
+        assert_eq!(
+            computer.run(&[0, 1, 5, 9]),
+            Err(ComputerError::InvalidOperand {
+                address: 2,
+                value: 9
+            })
+        );
+        assert_eq!(
+            computer.run(&[0, 1, 5]),
+            Err(ComputerError::OddLengthProgram { length: 3 })
+        );
+    }
+
+    #[test]
+    fn test_run_shift_overflow() {
+        // a = a / 2^b, anything past the width of the register cannot be shifted
+        let mut computer = Computer::new_with_registers(usize::MAX, 63, 0);
+        assert_eq!(computer.run(&[0, 5, 5, 4]), Ok(vec![1]));
+
+        computer.set_register_b(64);
+        assert_eq!(
+            computer.run(&[0, 5]),
+            Err(ComputerError::ShiftOverflow {
+                address: 0,
+                shift: 64
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_step_limit() {
+        // a never reaches zero, so the jump loops forever
+        let mut computer: Computer = Computer::new_with_registers(1, 0, 0).with_step_limit(100);
+        assert_eq!(
+            computer.run(&[5, 4, 3, 0]),
+            Err(ComputerError::StepLimitExceeded { limit: 100 })
+        );
+    }
+
+    #[test]
+    fn test_run_wide_registers() {
+        use super::super::word::BigUint;
+
+        // Prints every 3 bit digit of a, the lowest first
+        let program = [5, 4, 0, 3, 3, 0];
+        let a = 0o7654321076543210765432107654321u128;
+        let expected = (0..31)
+            .map(|i| (a >> (3 * i)) as usize & 7)
+            .collect::<Vec<_>>();
+
+        let mut computer = Computer::new_with_registers(a, 0, 0);
+        assert_eq!(computer.run(&program), Ok(expected.clone()));
+
+        let big = BigUint::from(a);
+        let mut computer =
+            Computer::new_with_registers(big, BigUint::default(), BigUint::default());
+        assert_eq!(computer.run(&program), Ok(expected));
+
+        // Shifting by 64 only overflows registers that are 64 bits wide
+        let mut computer = Computer::new_with_registers(u128::MAX, 64, 0);
+        assert_eq!(computer.run(&[0, 5, 5, 4]), Ok(vec![7]));
+        let mut computer = Computer::new_with_registers(u64::MAX, 64, 0);
+        assert_eq!(
+            computer.run(&[0, 5]),
+            Err(ComputerError::ShiftOverflow {
+                address: 0,
+                shift: 64
+            })
+        );
     }
 }
@@ -0,0 +1,445 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+
+use super::assembly::render_instruction;
+use super::computer::{Computer, ComputerError};
+use super::op_code::Instruction;
+
+// A single executed instruction, with the registers as they were after it ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub step: usize,
+    pub instruction_pointer: usize,
+    pub instruction: Instruction,
+    pub operand: usize,
+    pub registers: (usize, usize, usize),
+    pub output: Option<usize>,
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (a, b, c) = self.registers;
+        write!(
+            f,
+            "#{} {:02}: {:<6} a={} b={} c={}",
+            self.step,
+            self.instruction_pointer,
+            render_instruction(self.instruction.op_code(), Some(self.operand)),
+            a,
+            b,
+            c
+        )?;
+        if let Some(output) = self.output {
+            write!(f, " out={}", output)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Debugger {
+    computer: Computer,
+    program: Vec<usize>,
+    instruction_pointer: usize,
+    steps: usize,
+    output: Vec<usize>,
+    breakpoints: BTreeSet<usize>,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl Debugger {
+    pub fn new(computer: Computer, program: Vec<usize>) -> Self {
+        Self {
+            computer,
+            program,
+            instruction_pointer: 0,
+            steps: 0,
+            output: Vec::new(),
+            breakpoints: BTreeSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    pub fn registers(&self) -> (usize, usize, usize) {
+        self.computer.registers()
+    }
+
+    pub fn set_registers(&mut self, (a, b, c): (usize, usize, usize)) {
+        self.computer.set_register_a(a);
+        self.computer.set_register_b(b);
+        self.computer.set_register_c(c);
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn output(&self) -> &[usize] {
+        &self.output
+    }
+
+    pub fn program(&self) -> &[usize] {
+        &self.program
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.instruction_pointer + 1 >= self.program.len()
+    }
+
+    // The instruction that the next step executes, `None` once halted or if the op code is
+    // not a valid instruction
+    pub fn current_instruction(&self) -> Option<(Instruction, usize)> {
+        if self.is_halted() {
+            return None;
+        }
+
+        let ip = self.instruction_pointer;
+        Instruction::from_op_code(self.program[ip]).map(|i| (i, self.program[ip + 1]))
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &usize> {
+        self.breakpoints.iter()
+    }
+
+    // Starts recording a fresh trace, or stops and drops the recorded one
+    pub fn record_trace(&mut self, record: bool) {
+        self.trace = record.then(Vec::new);
+    }
+
+    pub fn trace(&self) -> Option<&[TraceEntry]> {
+        self.trace.as_deref()
+    }
+
+    // Returns false without doing anything once the program has halted. The instruction
+    // pointer is left on an instruction that fails. Steps count against the computer's step
+    // limit over the whole session, as they would in `Computer::run`
+    pub fn step(&mut self) -> Result<bool, ComputerError> {
+        if self.is_halted() {
+            return Ok(false);
+        }
+
+        let limit = self.computer.step_limit();
+        if self.steps == limit {
+            return Err(ComputerError::StepLimitExceeded { limit });
+        }
+
+        let ip = self.instruction_pointer;
+        let (next, output) = self.computer.step(&self.program, ip)?;
+
+        self.output.extend(output);
+        self.instruction_pointer = next;
+        self.steps += 1;
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry {
+                step: self.steps,
+                instruction_pointer: ip,
+                // The step succeeded, so the op code is valid
+                instruction: Instruction::from_op_code(self.program[ip]).unwrap(),
+                operand: self.program[ip + 1],
+                registers: self.computer.registers(),
+                output,
+            });
+        }
+
+        Ok(true)
+    }
+
+    // Returns how many steps were executed, fewer than `n` if the program halted
+    pub fn run_steps(&mut self, n: usize) -> Result<usize, ComputerError> {
+        let mut steps = 0;
+        while steps < n && self.step()? {
+            steps += 1;
+        }
+
+        Ok(steps)
+    }
+
+    // Always executes at least one step, so it can resume from a breakpoint. Returns the
+    // breakpoint that was hit or `None` if the program halted first
+    pub fn run_until_breakpoint(&mut self) -> Result<Option<usize>, ComputerError> {
+        while self.step()? {
+            if self.breakpoints.contains(&self.instruction_pointer) {
+                return Ok(Some(self.instruction_pointer));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn status(&self) -> String {
+        let (a, b, c) = self.registers();
+        let ip = self.instruction_pointer;
+        let next = if self.is_halted() {
+            "halted".to_string()
+        } else {
+            render_instruction(self.program[ip], Some(self.program[ip + 1]))
+        };
+        format!(
+            "ip={:02} a={} b={} c={} next: {}",
+            self.instruction_pointer, a, b, c, next
+        )
+    }
+
+    fn listing(&self) -> String {
+        let mut listing = String::new();
+
+        for (i, chunk) in self.program.chunks(2).enumerate() {
+            let address = i * 2;
+            let marker = if address == self.instruction_pointer {
+                '>'
+            } else {
+                ' '
+            };
+            let breakpoint = if self.breakpoints.contains(&address) {
+                '*'
+            } else {
+                ' '
+            };
+            let rendered = render_instruction(chunk[0], chunk.get(1).copied());
+            listing.push_str(&format!(
+                "{}{} {:02}: {}\n",
+                marker, breakpoint, address, rendered
+            ));
+        }
+
+        listing
+    }
+}
+
+const HELP: &str = "\
+commands:
+  s, step [n]            execute one or n instructions
+  c, continue            run until a breakpoint or the program halts
+  b, break <address>     set a breakpoint
+  d, delete <address>    remove a breakpoint
+  r, regs                show the instruction pointer and registers
+  set <a|b|c> <value>    change a register
+  o, out                 show the output so far
+  l, list                disassemble the program
+  t, trace [on|off]      start or stop recording, or print the recorded trace
+  h, help                show this message
+  q, quit                leave the debugger";
+
+// Reads one command per line until `quit` or the end of the input
+pub fn run_repl<R: BufRead, W: Write>(
+    debugger: &mut Debugger,
+    input: R,
+    mut output: W,
+) -> std::io::Result<()> {
+    writeln!(output, "{}", debugger.status())?;
+
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let argument = words.next();
+        let number = argument.and_then(|word| word.parse::<usize>().ok());
+
+        match (command, argument) {
+            ("s" | "step", _) => {
+                if let Err(error) = debugger.run_steps(number.unwrap_or(1)) {
+                    writeln!(output, "error: {}", error)?;
+                }
+                writeln!(output, "{}", debugger.status())?;
+            }
+            ("c" | "continue", _) => {
+                match debugger.run_until_breakpoint() {
+                    Ok(Some(address)) => writeln!(output, "breakpoint at {:02}", address)?,
+                    Ok(None) => writeln!(output, "halted after {} steps", debugger.steps())?,
+                    Err(error) => writeln!(output, "error: {}", error)?,
+                }
+                writeln!(output, "{}", debugger.status())?;
+            }
+            ("b" | "break", Some(_)) if number.is_some() => {
+                debugger.add_breakpoint(number.unwrap());
+            }
+            ("d" | "delete", Some(_)) if number.is_some() => {
+                if !debugger.remove_breakpoint(number.unwrap()) {
+                    writeln!(output, "no breakpoint at {}", argument.unwrap())?;
+                }
+            }
+            ("r" | "regs", _) => writeln!(output, "{}", debugger.status())?,
+            ("set", Some(register)) => {
+                let value = words.next().and_then(|word| word.parse::<usize>().ok());
+                let (a, b, c) = debugger.registers();
+                match (register, value) {
+                    ("a", Some(value)) => debugger.set_registers((value, b, c)),
+                    ("b", Some(value)) => debugger.set_registers((a, value, c)),
+                    ("c", Some(value)) => debugger.set_registers((a, b, value)),
+                    _ => {
+                        writeln!(output, "usage: set <a|b|c> <value>")?;
+                        continue;
+                    }
+                }
+                writeln!(output, "{}", debugger.status())?;
+            }
+            ("o" | "out", _) => {
+                let values = debugger.output().iter().map(|x| x.to_string());
+                writeln!(output, "{}", values.collect::<Vec<_>>().join(","))?;
+            }
+            ("l" | "list", _) => write!(output, "{}", debugger.listing())?,
+            ("t" | "trace", Some("on")) => debugger.record_trace(true),
+            ("t" | "trace", Some("off")) => debugger.record_trace(false),
+            ("t" | "trace", None) => match debugger.trace() {
+                Some(trace) => {
+                    for entry in trace {
+                        writeln!(output, "{}", entry)?;
+                    }
+                }
+                None => writeln!(output, "trace is off, start it with `trace on`")?,
+            },
+            ("h" | "help", _) => writeln!(output, "{}", HELP)?,
+            ("q" | "quit", _) => break,
+            _ => writeln!(output, "unknown command '{}', try `help`", line.trim())?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs a mod 8 and shifts a right by 3 until it reaches zero
+    fn debugger() -> Debugger {
+        Debugger::new(
+            Computer::new_with_registers(729, 0, 0),
+            vec![0, 1, 5, 4, 3, 0],
+        )
+    }
+
+    #[test]
+    fn test_step() {
+        let mut debugger = debugger();
+
+        assert_eq!(debugger.current_instruction(), Some((Instruction::Adv, 1)));
+        assert_eq!(debugger.step(), Ok(true));
+        assert_eq!(debugger.instruction_pointer(), 2);
+        assert_eq!(debugger.registers(), (364, 0, 0));
+
+        assert_eq!(debugger.step(), Ok(true));
+        assert_eq!(debugger.output(), &[4]);
+        assert_eq!(debugger.run_steps(2), Ok(2));
+        assert_eq!(debugger.instruction_pointer(), 2);
+    }
+
+    #[test]
+    fn test_run_until_breakpoint() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(4);
+
+        assert_eq!(debugger.run_until_breakpoint(), Ok(Some(4)));
+        assert_eq!(debugger.output(), &[4]);
+        assert_eq!(debugger.run_until_breakpoint(), Ok(Some(4)));
+        assert_eq!(debugger.output(), &[4, 6]);
+
+        assert!(debugger.remove_breakpoint(4));
+        assert_eq!(debugger.run_until_breakpoint(), Ok(None));
+        assert!(debugger.is_halted());
+        assert_eq!(debugger.step(), Ok(false));
+
+        let mut computer: Computer = Computer::new_with_registers(729, 0, 0);
+        assert_eq!(
+            Ok(debugger.output().to_vec()),
+            computer.run(&[0, 1, 5, 4, 3, 0])
+        );
+    }
+
+    #[test]
+    fn test_step_error() {
+        let mut debugger = Debugger::new(Computer::new_with_registers(1, 0, 0), vec![5, 4, 5, 7]);
+
+        assert_eq!(
+            debugger.run_steps(5),
+            Err(ComputerError::ReservedOperand { address: 2 })
+        );
+        assert_eq!(debugger.instruction_pointer(), 2);
+        assert_eq!(debugger.output(), &[1]);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // jnz 0 with a never changing keeps jumping back to itself
+        let computer = Computer::new_with_registers(1, 0, 0).with_step_limit(100);
+        let mut debugger = Debugger::new(computer, vec![3, 0]);
+        debugger.record_trace(true);
+
+        assert_eq!(
+            debugger.run_until_breakpoint(),
+            Err(ComputerError::StepLimitExceeded { limit: 100 })
+        );
+        assert_eq!(debugger.steps(), 100);
+        assert_eq!(debugger.trace().map(|trace| trace.len()), Some(100));
+        assert_eq!(
+            debugger.run_steps(1),
+            Err(ComputerError::StepLimitExceeded { limit: 100 })
+        );
+
+        // Changing registers keeps the limit the computer was built with
+        let computer = Computer::new_with_registers(0, 0, 0).with_step_limit(5);
+        let mut debugger = Debugger::new(computer, vec![3, 0]);
+        debugger.set_registers((1, 2, 3));
+        assert_eq!(debugger.registers(), (1, 2, 3));
+        assert_eq!(
+            debugger.run_until_breakpoint(),
+            Err(ComputerError::StepLimitExceeded { limit: 5 })
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut debugger = debugger();
+        debugger.record_trace(true);
+        debugger.run_steps(3).unwrap();
+
+        let trace = debugger.trace().unwrap();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[1].output, Some(4));
+        assert_eq!(trace[2].instruction, Instruction::Jnz);
+        assert_eq!(trace[2].registers, (364, 0, 0));
+        assert_eq!(trace[1].to_string(), "#2 02: out a  a=364 b=0 c=0 out=4");
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut debugger = debugger();
+        let script = "b 4\ntrace on\ncontinue\nset a 8\nc\nout\ntrace\nbogus\nquit\nstep\n";
+        let mut output = Vec::new();
+
+        run_repl(&mut debugger, script.as_bytes(), &mut output).unwrap();
+
+        let expected = "\
+ip=00 a=729 b=0 c=0 next: adv 1
+breakpoint at 04
+ip=04 a=364 b=0 c=0 next: jnz 0
+ip=04 a=8 b=0 c=0 next: jnz 0
+breakpoint at 04
+ip=04 a=4 b=0 c=0 next: jnz 0
+4,4
+#1 00: adv 1  a=364 b=0 c=0
+#2 02: out a  a=364 b=0 c=0 out=4
+#3 04: jnz 0  a=8 b=0 c=0
+#4 00: adv 1  a=4 b=0 c=0
+#5 02: out a  a=4 b=0 c=0 out=4
+unknown command 'bogus', try `help`
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+        assert_eq!(debugger.steps(), 5);
+    }
+}
@@ -0,0 +1,518 @@
+use std::fmt::Display;
+
+use super::computer::ComputerError;
+use super::op_code::Instruction;
+
+// Lifts a program into statements over the registers. Every instruction of a block is
+// evaluated symbolically, so each output and each register written by the block becomes an
+// expression of the register values at the start of the block. The only control flow
+// understood is a single `jnz` at the end of the program, which becomes a do-while loop
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecompileError {
+    InvalidProgram(ComputerError),
+    // A jump anywhere but the end of the program, or forwards
+    UnsupportedJump { address: usize },
+}
+
+impl Display for DecompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidProgram(error) => write!(f, "invalid program: {}", error),
+            Self::UnsupportedJump { address } => {
+                write!(f, "address {}: jump does not close a loop", address)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecompileError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    A,
+    B,
+    C,
+}
+
+const REGISTERS: [Register; 3] = [Register::A, Register::B, Register::C];
+
+impl Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::A => write!(f, "a"),
+            Self::B => write!(f, "b"),
+            Self::C => write!(f, "c"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Register(Register),
+    Literal(usize),
+    Shr(Box<Expr>, Box<Expr>),
+    // Flattened, with all literals folded into one trailing term
+    Xor(Vec<Expr>),
+    And(Box<Expr>, usize),
+}
+
+fn shift_right(value: usize, shift: usize) -> Option<usize> {
+    u32::try_from(shift)
+        .ok()
+        .and_then(|shift| value.checked_shr(shift))
+}
+
+impl Expr {
+    fn shr(value: Expr, shift: Expr) -> Expr {
+        match (value, shift) {
+            (value, Expr::Literal(0)) => value,
+            (Expr::Literal(value), Expr::Literal(shift)) if shift < usize::BITS as usize => {
+                Expr::Literal(value >> shift)
+            }
+            // (a >> 1) >> 2 is a >> 3
+            (Expr::Shr(value, inner), Expr::Literal(shift)) => match *inner {
+                Expr::Literal(inner) => Expr::Shr(value, Box::new(Expr::Literal(inner + shift))),
+                inner => Expr::Shr(
+                    Box::new(Expr::Shr(value, Box::new(inner))),
+                    Box::new(Expr::Literal(shift)),
+                ),
+            },
+            (value, shift) => Expr::Shr(Box::new(value), Box::new(shift)),
+        }
+    }
+
+    fn xor(left: Expr, right: Expr) -> Expr {
+        let mut terms = Vec::new();
+        let mut literal = 0;
+
+        for term in [left, right] {
+            let flattened = match term {
+                Expr::Xor(terms) => terms,
+                term => vec![term],
+            };
+
+            for term in flattened {
+                match term {
+                    Expr::Literal(value) => literal ^= value,
+                    term => terms.push(term),
+                }
+            }
+        }
+
+        if literal != 0 || terms.is_empty() {
+            terms.push(Expr::Literal(literal));
+        }
+
+        match terms.len() {
+            1 => terms.pop().unwrap(),
+            _ => Expr::Xor(terms),
+        }
+    }
+
+    // Masks with a run of low bits, skipped when the value already fits in them
+    fn and(value: Expr, mask: usize) -> Expr {
+        match value {
+            Expr::Literal(value) => Expr::Literal(value & mask),
+            Expr::And(value, inner) => Expr::And(value, inner & mask),
+            value if value.max_value().is_some_and(|max| max <= mask) => value,
+            value => Expr::And(Box::new(value), mask),
+        }
+    }
+
+    // An upper bound when the value can be bounded at all
+    fn max_value(&self) -> Option<usize> {
+        match self {
+            Expr::Register(_) => None,
+            Expr::Literal(value) => Some(*value),
+            Expr::Shr(value, _) => value.max_value(),
+            // Every bit up to the highest one of any term may be set
+            Expr::Xor(terms) => terms.iter().try_fold(0, |max, term| {
+                let zeros = term.max_value()?.leading_zeros();
+                Some(max | usize::MAX.checked_shr(zeros).unwrap_or(0))
+            }),
+            Expr::And(_, mask) => Some(*mask),
+        }
+    }
+
+    pub fn references(&self, register: Register) -> bool {
+        match self {
+            Expr::Register(r) => *r == register,
+            Expr::Literal(_) => false,
+            Expr::Shr(value, shift) => value.references(register) || shift.references(register),
+            Expr::Xor(terms) => terms.iter().any(|term| term.references(register)),
+            Expr::And(value, _) => value.references(register),
+        }
+    }
+
+    // None when a shift overflows, which halts the computer
+    pub fn evaluate(&self, registers: [usize; 3]) -> Option<usize> {
+        match self {
+            Expr::Register(r) => Some(registers[*r as usize]),
+            Expr::Literal(value) => Some(*value),
+            Expr::Shr(value, shift) => {
+                shift_right(value.evaluate(registers)?, shift.evaluate(registers)?)
+            }
+            Expr::Xor(terms) => terms
+                .iter()
+                .try_fold(0, |acc, term| Some(acc ^ term.evaluate(registers)?)),
+            Expr::And(value, mask) => Some(value.evaluate(registers)? & mask),
+        }
+    }
+
+    fn is_compound(&self) -> bool {
+        matches!(self, Expr::Shr(..) | Expr::Xor(_) | Expr::And(..))
+    }
+}
+
+// Nested operations are always parenthesised rather than relying on precedence
+struct Operand<'a>(&'a Expr);
+
+impl Display for Operand<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_compound() {
+            write!(f, "({})", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Register(r) => write!(f, "{}", r),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Shr(value, shift) => write!(f, "{} >> {}", Operand(value), Operand(shift)),
+            Expr::Xor(terms) => {
+                for (i, term) in terms.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ^ ")?;
+                    }
+                    write!(f, "{}", Operand(term))?;
+                }
+                Ok(())
+            }
+            Expr::And(value, mask) => write!(f, "{} & {}", Operand(value), mask),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Out(Expr),
+    // Simultaneous, every expression reads the registers from before the assignment
+    Assign(Vec<(Register, Expr)>),
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Statement::Out(value) => write!(f, "out({})", value),
+            Statement::Assign(assignments) if assignments.len() == 1 => {
+                write!(f, "{} = {}", assignments[0].0, assignments[0].1)
+            }
+            Statement::Assign(assignments) => {
+                let (registers, values): (Vec<_>, Vec<_>) = assignments
+                    .iter()
+                    .map(|(r, value)| (r.to_string(), value.to_string()))
+                    .unzip();
+                write!(f, "({}) = ({})", registers.join(", "), values.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decompiled {
+    // Runs once, before the loop if there is one
+    pub prologue: Vec<Statement>,
+    // Repeated while a is not zero
+    pub body: Option<Vec<Statement>>,
+}
+
+impl Display for Decompiled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for statement in &self.prologue {
+            writeln!(f, "{}", statement)?;
+        }
+
+        if let Some(body) = &self.body {
+            writeln!(f, "do {{")?;
+            for statement in body {
+                writeln!(f, "    {}", statement)?;
+            }
+            writeln!(f, "}} while a != 0")?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Block {
+    registers: [Expr; 3],
+    outputs: Vec<Expr>,
+}
+
+impl Block {
+    fn evaluate(instructions: &[(Instruction, usize)]) -> Self {
+        use Instruction::*;
+
+        let mut registers = REGISTERS.map(Expr::Register);
+        let mut outputs = Vec::new();
+
+        for (instruction, operand) in instructions {
+            let combo = match operand {
+                4..=6 => registers[operand - 4].clone(),
+                _ => Expr::Literal(*operand),
+            };
+            let [a, b, c] = &registers;
+
+            match instruction {
+                Adv => registers[0] = Expr::shr(a.clone(), combo),
+                Bxl => registers[1] = Expr::xor(b.clone(), Expr::Literal(*operand)),
+                Bst => registers[1] = Expr::and(combo, 7),
+                Bxc => registers[1] = Expr::xor(b.clone(), c.clone()),
+                Out => outputs.push(Expr::and(combo, 7)),
+                Bdv => registers[1] = Expr::shr(a.clone(), combo),
+                Cdv => registers[2] = Expr::shr(a.clone(), combo),
+                Jnz => unreachable!("jumps end a block"),
+            }
+        }
+
+        Self { registers, outputs }
+    }
+
+    // Registers read by the block when `live` are needed after it
+    fn live_in(&self, live: [bool; 3]) -> [bool; 3] {
+        REGISTERS.map(|register| {
+            self.outputs.iter().any(|value| value.references(register))
+                || REGISTERS
+                    .iter()
+                    .any(|r| live[*r as usize] && self.registers[*r as usize].references(register))
+        })
+    }
+
+    fn statements(self, live: [bool; 3]) -> Vec<Statement> {
+        let assignments = REGISTERS
+            .into_iter()
+            .zip(self.registers)
+            .filter(|(r, value)| live[*r as usize] && *value != Expr::Register(*r))
+            .collect::<Vec<_>>();
+
+        let mut statements = self
+            .outputs
+            .into_iter()
+            .map(Statement::Out)
+            .collect::<Vec<_>>();
+        if !assignments.is_empty() {
+            statements.push(Statement::Assign(assignments));
+        }
+        statements
+    }
+}
+
+pub fn decompile(program: &[usize]) -> Result<Decompiled, DecompileError> {
+    if !program.len().is_multiple_of(2) {
+        return Err(DecompileError::InvalidProgram(
+            ComputerError::OddLengthProgram {
+                length: program.len(),
+            },
+        ));
+    }
+
+    let mut instructions = Vec::with_capacity(program.len() / 2);
+    for (i, chunk) in program.chunks_exact(2).enumerate() {
+        let address = i * 2;
+        let instruction = Instruction::from_op_code(chunk[0]).ok_or(
+            DecompileError::InvalidProgram(ComputerError::InvalidOpCode {
+                address,
+                value: chunk[0],
+            }),
+        )?;
+
+        let operand = chunk[1];
+        if instruction.has_combo_operand() && operand >= 7 {
+            return Err(DecompileError::InvalidProgram(match operand {
+                7 => ComputerError::ReservedOperand { address },
+                _ => ComputerError::InvalidOperand {
+                    address,
+                    value: operand,
+                },
+            }));
+        }
+
+        instructions.push((instruction, operand));
+    }
+
+    // The loop runs from the jump target up to a `jnz` closing the program
+    let mut loop_start = None;
+    for (i, (instruction, target)) in instructions.iter().enumerate() {
+        if *instruction != Instruction::Jnz {
+            continue;
+        }
+
+        let closes_loop =
+            i + 1 == instructions.len() && target.is_multiple_of(2) && *target <= i * 2;
+        if !closes_loop {
+            return Err(DecompileError::UnsupportedJump { address: i * 2 });
+        }
+        loop_start = Some(target / 2);
+    }
+
+    let (prologue, body) = match loop_start {
+        Some(start) => (
+            &instructions[..start],
+            Some(&instructions[start..instructions.len() - 1]),
+        ),
+        None => (&instructions[..], None),
+    };
+
+    let (body, live) = match body {
+        Some(body) => {
+            let body = Block::evaluate(body);
+
+            // a is always read by the jump, anything carried across iterations is live too
+            let mut live = [true, false, false];
+            loop {
+                let mut next = body.live_in(live);
+                next[0] = true;
+                if next == live {
+                    break;
+                }
+                live = next;
+            }
+
+            (Some(body.statements(live)), live)
+        }
+        None => (None, [false; 3]),
+    };
+
+    Ok(Decompiled {
+        prologue: Block::evaluate(prologue).statements(live),
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::computer::Computer;
+    use super::*;
+
+    const PROGRAM: [usize; 16] = [2, 4, 1, 1, 7, 5, 1, 5, 4, 2, 5, 5, 0, 3, 3, 0];
+
+    fn execute(statements: &[Statement], registers: &mut [usize; 3], output: &mut Vec<usize>) {
+        for statement in statements {
+            match statement {
+                Statement::Out(value) => output.push(value.evaluate(*registers).unwrap()),
+                Statement::Assign(assignments) => {
+                    let values = assignments
+                        .iter()
+                        .map(|(r, value)| (*r, value.evaluate(*registers).unwrap()))
+                        .collect::<Vec<_>>();
+                    for (r, value) in values {
+                        registers[r as usize] = value;
+                    }
+                }
+            }
+        }
+    }
+
+    // Runs the decompiled statements, which has to agree with the computer
+    fn interpret(decompiled: &Decompiled, registers: [usize; 3]) -> Vec<usize> {
+        let mut registers = registers;
+        let mut output = Vec::new();
+
+        execute(&decompiled.prologue, &mut registers, &mut output);
+        if let Some(body) = &decompiled.body {
+            loop {
+                execute(body, &mut registers, &mut output);
+                if registers[0] == 0 {
+                    break;
+                }
+            }
+        }
+
+        output
+    }
+
+    #[test]
+    fn test_decompile() {
+        let decompiled = decompile(&PROGRAM).unwrap();
+        let expected = "\
+do {
+    out(((a & 7) ^ (a >> ((a & 7) ^ 1)) ^ 4) & 7)
+    a = a >> 3
+} while a != 0
+";
+        assert_eq!(decompiled.to_string(), expected);
+
+        let quine = decompile(&[0, 3, 5, 4, 3, 0]).unwrap();
+        assert_eq!(
+            quine.to_string(),
+            "do {\n    out((a >> 3) & 7)\n    a = a >> 3\n} while a != 0\n"
+        );
+    }
+
+    #[test]
+    fn test_decompile_matches_computer() {
+        let programs: [&[usize]; 4] = [
+            &PROGRAM,
+            &[0, 1, 5, 4, 0, 1, 5, 4, 3, 0],
+            // b carries across iterations, so it is assigned in the loop
+            &[1, 1, 5, 5, 0, 3, 3, 0],
+            // A prologue sets c before the loop starts at 4
+            &[2, 4, 7, 5, 5, 6, 4, 0, 1, 3, 5, 5, 0, 2, 3, 4],
+        ];
+
+        for program in programs {
+            let decompiled = decompile(program).unwrap();
+            for a in [1, 7, 100, 28422061, 1 << 40] {
+                for (b, c) in [(0, 0), (5, 3)] {
+                    let expected = Computer::new_with_registers(a, b, c).run(program).unwrap();
+                    assert_eq!(interpret(&decompiled, [a, b, c]), expected, "{:?}", program);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompile_carried_registers() {
+        let decompiled = decompile(&[1, 1, 5, 5, 0, 3, 3, 0]).unwrap();
+        assert_eq!(
+            decompiled.to_string(),
+            "do {\n    out((b ^ 1) & 7)\n    (a, b) = (a >> 3, b ^ 1)\n} while a != 0\n"
+        );
+
+        // Straight line programs need no assignments at the end
+        let decompiled = decompile(&[5, 4, 0, 1, 5, 4]).unwrap();
+        assert_eq!(decompiled.to_string(), "out(a & 7)\nout((a >> 1) & 7)\n");
+    }
+
+    #[test]
+    fn test_decompile_errors() {
+        assert_eq!(
+            decompile(&[3, 4, 5, 4]),
+            Err(DecompileError::UnsupportedJump { address: 0 })
+        );
+        assert_eq!(
+            decompile(&[5, 4, 3, 6]),
+            Err(DecompileError::UnsupportedJump { address: 2 })
+        );
+        assert_eq!(
+            decompile(&[5, 7]),
+            Err(DecompileError::InvalidProgram(
+                ComputerError::ReservedOperand { address: 0 }
+            ))
+        );
+        assert_eq!(
+            decompile(&[5, 4, 9, 0]),
+            Err(DecompileError::InvalidProgram(
+                ComputerError::InvalidOpCode {
+                    address: 2,
+                    value: 9
+                }
+            ))
+        );
+    }
+}
@@ -1,6 +1,17 @@
-use computer::Computer;
+mod assembly;
 mod computer;
+mod debugger;
+mod decompiler;
 mod op_code;
+mod solver;
+mod word;
+
+pub use assembly::{assemble, disassemble, AssemblyError};
+pub use computer::{Computer, ComputerError};
+pub use debugger::{run_repl, Debugger, TraceEntry};
+pub use decompiler::{decompile, DecompileError, Decompiled, Expr, Register, Statement};
+pub use solver::{SolveError, Strategy, TargetSolver};
+pub use word::{BigUint, Word};
 
 fn parse_input(input: &str) -> (Computer, Vec<usize>) {
     let mut computer = Computer::new();
@@ -29,38 +40,39 @@ fn parse_input(input: &str) -> (Computer, Vec<usize>) {
     (computer, program)
 }
 
-pub fn get_total_output_from_computer(input: &str) -> Vec<usize> {
+pub fn get_total_output_from_computer(input: &str) -> Result<Vec<usize>, ComputerError> {
     let (mut computer, program) = parse_input(input);
     computer.run(&program)
 }
 
-pub fn find_a_for_quine_sequence(input: &str) -> usize {
+pub fn disassemble_input(input: &str) -> String {
     let (_, program) = parse_input(input);
+    disassemble(&program)
+}
 
-    let slice = program.as_slice();
-
-    let mut state = Vec::from([(0, 0)]);
-    let mut result = usize::MAX;
-
-    while let Some((a, i)) = state.pop() {
-        if i == slice.len() {
-            result = result.min(a);
-            continue;
-        }
+pub fn decompile_input(input: &str) -> Result<Decompiled, DecompileError> {
+    let (_, program) = parse_input(input);
+    decompile(&program)
+}
 
-        for b in 0..8 {
-            let new_a = (a * 8) + b;
-            let mut computer = Computer::new_with_registers(new_a, 0, 0);
-            let output = computer.run(slice);
+pub fn debugger_from_input(input: &str) -> Debugger {
+    let (computer, program) = parse_input(input);
+    Debugger::new(computer, program)
+}
 
-            if output[0] == slice[slice.len() - 1 - i] {
-                state.push((new_a, i + 1));
-            }
-        }
-    }
+// The smallest register a that makes the program print `target`, keeping b and c from the input
+pub fn find_a_for_output(input: &str, target: &[usize]) -> Result<usize, SolveError> {
+    let (computer, program) = parse_input(input);
+    let (_, b, c) = computer.registers();
+    TargetSolver::new(&program, target).registers(b, c).solve()
+}
 
-    result
+// Long programs need an a wider than usize, any register width can be searched
+pub fn find_a_for_quine_sequence<R: Word>(input: &str) -> Result<R, SolveError> {
+    let (_, program) = parse_input(input);
+    TargetSolver::new_wide(&program, &program).solve()
 }
+
 #[cfg(test)]
 mod tests {
 
@@ -84,12 +96,53 @@ Program: 0,3,5,4,3,0
     #[test]
     fn test_get_total_output_from_computer() {
         let result = get_total_output_from_computer(TEST_INPUT);
-        assert_eq!(result, vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+        assert_eq!(result, Ok(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]));
     }
 
     #[test]
     fn test_find_a_for_quine_sequence() {
-        let result = find_a_for_quine_sequence(QUINE_TEST_INPUT);
-        assert_eq!(result, 117440);
+        let result = find_a_for_quine_sequence::<usize>(QUINE_TEST_INPUT);
+        assert_eq!(result, Ok(117440));
+    }
+
+    #[test]
+    fn test_find_a_for_quine_sequence_wide() {
+        // Outputs the 3 bit digits of a above the lowest, 28 of them need 87 bits
+        let input = "Program: 0,3,2,4,1,0,1,1,1,2,1,3,1,4,1,5,1,6,1,7,7,5,4,0,5,4,3,0";
+        let (_, program) = parse_input(input);
+        let expected = program
+            .iter()
+            .rev()
+            .fold(0u128, |a, value| (a << 3) | *value as u128)
+            << 3;
+
+        assert_eq!(find_a_for_quine_sequence::<u128>(input), Ok(expected));
+        assert_eq!(
+            find_a_for_quine_sequence::<BigUint>(input),
+            Ok(BigUint::from(expected))
+        );
+
+        let output = Computer::new_with_registers(
+            BigUint::from(expected),
+            BigUint::default(),
+            BigUint::default(),
+        )
+        .run(&program);
+        assert_eq!(output.as_ref(), Ok(&program));
+
+        // 87 bits do not fit, so the narrow search finds nothing
+        assert_eq!(
+            TargetSolver::new(&program, &program).solve(),
+            Err(SolveError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_find_a_for_output() {
+        assert_eq!(find_a_for_output(QUINE_TEST_INPUT, &[3, 0]), Ok(24));
+        assert_eq!(
+            find_a_for_output(QUINE_TEST_INPUT, &[1]),
+            Err(SolveError::NoSolution)
+        );
     }
 }
@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     Adv,
     Bxl,
@@ -10,18 +10,49 @@ pub enum Instruction {
     Cdv,
 }
 
-impl From<usize> for Instruction {
-    fn from(value: usize) -> Self {
+impl Instruction {
+    pub fn from_op_code(value: usize) -> Option<Self> {
         match value {
-            0 => Self::Adv,
-            1 => Self::Bxl,
-            2 => Self::Bst,
-            3 => Self::Jnz,
-            4 => Self::Bxc,
-            5 => Self::Out,
-            6 => Self::Bdv,
-            7 => Self::Cdv,
-            _ => panic!("Invalid OpCode"),
+            0 => Some(Self::Adv),
+            1 => Some(Self::Bxl),
+            2 => Some(Self::Bst),
+            3 => Some(Self::Jnz),
+            4 => Some(Self::Bxc),
+            5 => Some(Self::Out),
+            6 => Some(Self::Bdv),
+            7 => Some(Self::Cdv),
+            _ => None,
         }
     }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Adv => "adv",
+            Self::Bxl => "bxl",
+            Self::Bst => "bst",
+            Self::Jnz => "jnz",
+            Self::Bxc => "bxc",
+            Self::Out => "out",
+            Self::Bdv => "bdv",
+            Self::Cdv => "cdv",
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        (0..8)
+            .filter_map(Self::from_op_code)
+            .find(|instruction| instruction.mnemonic() == mnemonic)
+    }
+
+    // The rest take a literal operand, `bxc` reads one but ignores it
+    pub fn has_combo_operand(&self) -> bool {
+        matches!(
+            self,
+            Self::Adv | Self::Bst | Self::Out | Self::Bdv | Self::Cdv
+        )
+    }
+
+    pub fn op_code(&self) -> usize {
+        *self as usize
+    }
 }
@@ -0,0 +1,581 @@
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use super::computer::{Computer, ComputerError, DEFAULT_STEP_LIMIT};
+use super::op_code::Instruction;
+use super::word::Word;
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    InvalidProgram(ComputerError),
+    // The whole search space was exhausted
+    NoSolution,
+    // The bit search gave up after trying `limit` candidates for the low bits of a
+    SearchLimitReached { limit: usize },
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidProgram(error) => write!(f, "invalid program: {}", error),
+            Self::NoSolution => write!(f, "no value of a produces the target output"),
+            Self::SearchLimitReached { limit } => {
+                write!(
+                    f,
+                    "gave up after {} candidates for the low bits of a",
+                    limit
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // The program is a single loop ending in `jnz 0`, each iteration shifts a right by
+    // `shift` bits, outputs `outputs` values and only reads b and c after writing them.
+    // Every iteration then depends on a alone, so a is built from the last output backwards
+    ShiftLoop { shift: usize, outputs: usize },
+    // Anything else, a is fixed one low bit at a time, dropping the low bits for which
+    // running the program gives a wrong output before the unknown high bits are needed
+    BitSearch,
+}
+
+// Registers are usize unless built with `new_wide`
+pub struct TargetSolver<'a, R = usize> {
+    program: &'a [usize],
+    target: &'a [usize],
+    b: R,
+    c: R,
+    search_limit: usize,
+    step_limit: usize,
+}
+
+impl<'a> TargetSolver<'a> {
+    pub fn new(program: &'a [usize], target: &'a [usize]) -> Self {
+        Self::new_wide(program, target)
+    }
+}
+
+impl<'a, R: Word> TargetSolver<'a, R> {
+    pub fn new_wide(program: &'a [usize], target: &'a [usize]) -> Self {
+        Self {
+            program,
+            target,
+            b: R::from_usize(0),
+            c: R::from_usize(0),
+            search_limit: DEFAULT_SEARCH_LIMIT,
+            step_limit: DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    pub fn registers(mut self, b: R, c: R) -> Self {
+        self.b = b;
+        self.c = c;
+        self
+    }
+
+    pub fn search_limit(mut self, search_limit: usize) -> Self {
+        self.search_limit = search_limit;
+        self
+    }
+
+    // Every run of the program, whole or up to the unknown bits of a, stops after this many steps
+    pub fn step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        analyse(self.program).unwrap_or(Strategy::BitSearch)
+    }
+
+    // The smallest a that makes the program output exactly the target
+    pub fn solve(&self) -> Result<R, SolveError> {
+        validate(self.program)?;
+
+        match self.strategy() {
+            Strategy::ShiftLoop { shift, outputs } => self.solve_shift_loop(shift, outputs),
+            Strategy::BitSearch => self
+                .solve_bit_search(&[(self.b.clone(), self.c.clone())])
+                .map(|(a, _, _)| a),
+        }
+    }
+
+    // Also picks b and c from `values`, returns the smallest (a, b, c). Shift loops never
+    // read the initial b and c, so the smallest values are returned for them
+    pub fn solve_with_free_registers(
+        &self,
+        values: RangeInclusive<usize>,
+    ) -> Result<(R, R, R), SolveError> {
+        let lowest = *values.start();
+        if let Strategy::ShiftLoop { .. } = self.strategy() {
+            return self
+                .solve()
+                .map(|a| (a, R::from_usize(lowest), R::from_usize(lowest)));
+        }
+
+        validate(self.program)?;
+
+        // Every pair is searched at once, so the search stops at the shortest a of any pair
+        let registers = values
+            .clone()
+            .flat_map(|b| values.clone().map(move |c| (b, c)))
+            .map(|(b, c)| (R::from_usize(b), R::from_usize(c)))
+            .collect::<Vec<_>>();
+        self.solve_bit_search(&registers)
+    }
+
+    fn solve_shift_loop(&self, shift: usize, outputs: usize) -> Result<R, SolveError> {
+        if self.target.is_empty() || !self.target.len().is_multiple_of(outputs) {
+            return Err(SolveError::NoSolution);
+        }
+
+        let iterations = self.target.len() / outputs;
+        let jump = self.program.len() - 2;
+
+        // (a >> (shift * iteration), iteration), iterations are assigned from the last one,
+        // the smallest candidates are popped first so the first full match is the minimum
+        let mut stack = vec![(R::from_usize(0), iterations)];
+
+        while let Some((rest, iteration)) = stack.pop() {
+            if iteration == 0 {
+                if self.run(&rest).is_some_and(|output| output == self.target) {
+                    return Ok(rest);
+                }
+                continue;
+            }
+
+            // The loop only reaches this iteration if a was non zero before it
+            if iteration < iterations && rest.is_zero() {
+                continue;
+            }
+
+            let i = iteration - 1;
+            let expected = &self.target[i * outputs..(i + 1) * outputs];
+
+            for digit in (0..1 << shift).rev() {
+                let Some(a) = rest.shift_left_or(shift, digit) else {
+                    continue;
+                };
+
+                if self.run_iteration(&a, jump).as_deref() == Some(expected) {
+                    stack.push((a, i));
+                }
+            }
+        }
+
+        Err(SolveError::NoSolution)
+    }
+
+    // The smallest (a, b, c) for any of the (b, c) `registers`. Each round fixes one more low
+    // bit of a and keeps the low bits that can still produce the target, an a whose highest set
+    // bit is the one just fixed is run in full. Rounds go up in the length of a, so the first
+    // round with a match holds the minimum
+    fn solve_bit_search(&self, registers: &[(R, R)]) -> Result<(R, R, R), SolveError> {
+        // (low bits of a, position in `registers`)
+        let mut frontier = (0..registers.len())
+            .map(|i| (R::from_usize(0), i))
+            .collect::<Vec<_>>();
+        let mut candidates = frontier.clone();
+        let mut tried = 0;
+
+        for bits in 0.. {
+            let best = candidates
+                .iter()
+                .filter(|(a, i)| {
+                    let (b, c) = &registers[*i];
+                    self.run_with(a, b, c)
+                        .is_some_and(|output| output == self.target)
+                })
+                .map(|(a, i)| (a.clone(), registers[*i].0.clone(), registers[*i].1.clone()))
+                .min();
+            if let Some(best) = best {
+                return Ok(best);
+            }
+
+            frontier.retain(|(low, i)| {
+                let (b, c) = &registers[*i];
+                self.worth_extending(low, bits, b, c)
+            });
+
+            // Past the width of the register no longer a fits
+            let Some(bit) = R::from_usize(1).shift_left_or(bits, 0) else {
+                break;
+            };
+            if frontier.is_empty() {
+                break;
+            }
+
+            tried += frontier.len();
+            if tried > self.search_limit {
+                return Err(SolveError::SearchLimitReached {
+                    limit: self.search_limit,
+                });
+            }
+
+            candidates = frontier
+                .iter()
+                .map(|(low, i)| (low.xor(&bit), *i))
+                .collect();
+            frontier.extend(candidates.iter().cloned());
+        }
+
+        Err(SolveError::NoSolution)
+    }
+
+    // Runs the program for every a with these `bits` low bits at once, until a value depends
+    // on the unknown high bits. Only then is it worth fixing another bit: an output that
+    // differs from the target rules out every such a, and a run that gets to the end without
+    // the high bits gives the same output for all of them, which was already tried for the
+    // a without high bits
+    fn worth_extending(&self, low: &R, bits: usize, b: &R, c: &R) -> bool {
+        use Instruction::*;
+
+        let mut a = Partial::new(low.clone(), Some(bits));
+        let mut b = Partial::new(b.clone(), None);
+        let mut c = Partial::new(c.clone(), None);
+        let mut matched = 0;
+        let mut instruction_pointer = 0;
+
+        for _ in 0..self.step_limit {
+            if instruction_pointer + 1 >= self.program.len() {
+                return false;
+            }
+
+            // The op codes were validated, a reserved or invalid operand fails every a
+            let instruction = Instruction::from_op_code(self.program[instruction_pointer]).unwrap();
+            let operand = self.program[instruction_pointer + 1];
+            let combo = match operand {
+                0..=3 => Partial::new(R::from_usize(operand), None),
+                4 => a.clone(),
+                5 => b.clone(),
+                6 => c.clone(),
+                _ if instruction.has_combo_operand() => return false,
+                _ => Partial::new(R::from_usize(0), None),
+            };
+
+            match instruction {
+                Adv | Bdv | Cdv => {
+                    if combo.known.is_some() {
+                        return true;
+                    }
+                    let Some(value) = a.value.shift_right(&combo.value) else {
+                        return false;
+                    };
+                    let shift = combo.value.to_usize().unwrap_or(usize::MAX);
+                    let result = Partial::new(value, a.known.map(|k| k.saturating_sub(shift)));
+
+                    match instruction {
+                        Adv => a = result,
+                        Bdv => b = result,
+                        _ => c = result,
+                    }
+                }
+                Bxl => b.value = b.value.xor(&R::from_usize(operand)),
+                Bst => {
+                    b = Partial::new(
+                        R::from_usize(combo.value.low_bits(0x7)),
+                        combo.known.filter(|k| *k < 3),
+                    )
+                }
+                // The known bits of a are all there is to it, any set one means a is not zero
+                Jnz if !a.value.is_zero() => {
+                    instruction_pointer = operand;
+                    continue;
+                }
+                Jnz if a.known.is_some() => return true,
+                Jnz => {}
+                Bxc => {
+                    let known = match (b.known, c.known) {
+                        (Some(x), Some(y)) => Some(x.min(y)),
+                        (x, y) => x.or(y),
+                    };
+                    b = Partial::new(b.value.xor(&c.value), known);
+                }
+                Out => {
+                    if combo.known.is_some_and(|k| k < 3) {
+                        return true;
+                    }
+                    if self.target.get(matched) != Some(&combo.value.low_bits(0x7)) {
+                        return false;
+                    }
+                    matched += 1;
+                }
+            }
+
+            instruction_pointer += 2;
+        }
+
+        // Out of steps, some a may still halt sooner
+        true
+    }
+
+    fn run(&self, a: &R) -> Option<Vec<usize>> {
+        self.run_with(a, &self.b, &self.c)
+    }
+
+    fn run_with(&self, a: &R, b: &R, c: &R) -> Option<Vec<usize>> {
+        let mut computer = Computer::new_with_registers(a.clone(), b.clone(), c.clone())
+            .with_step_limit(self.step_limit);
+        computer.run(self.program).ok()
+    }
+
+    // Runs the loop body once, up to the closing `jnz`
+    fn run_iteration(&self, a: &R, jump: usize) -> Option<Vec<usize>> {
+        let mut computer = Computer::new_with_registers(a.clone(), self.b.clone(), self.c.clone());
+        let mut output = Vec::new();
+        let mut instruction_pointer = 0;
+
+        while instruction_pointer < jump {
+            let (next, value) = computer.step(self.program, instruction_pointer).ok()?;
+            output.extend(value);
+            instruction_pointer = next;
+        }
+
+        Some(output)
+    }
+}
+
+// A register known only in its lowest `known` bits, fully known when None. The bits of a above
+// them are zero in `value`, b and c may hold anything there
+#[derive(Clone)]
+struct Partial<R> {
+    value: R,
+    known: Option<usize>,
+}
+
+impl<R> Partial<R> {
+    fn new(value: R, known: Option<usize>) -> Self {
+        Self { value, known }
+    }
+}
+
+fn validate(program: &[usize]) -> Result<(), SolveError> {
+    if !program.len().is_multiple_of(2) {
+        return Err(SolveError::InvalidProgram(
+            ComputerError::OddLengthProgram {
+                length: program.len(),
+            },
+        ));
+    }
+
+    for (address, value) in program.iter().enumerate().step_by(2) {
+        if Instruction::from_op_code(*value).is_none() {
+            return Err(SolveError::InvalidProgram(ComputerError::InvalidOpCode {
+                address,
+                value: *value,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+fn analyse(program: &[usize]) -> Option<Strategy> {
+    use Instruction::*;
+
+    let instructions = program
+        .chunks_exact(2)
+        .map(|chunk| Some((Instruction::from_op_code(chunk[0])?, chunk[1])))
+        .collect::<Option<Vec<_>>>()?;
+
+    let (last, body) = instructions.split_last()?;
+    if *last != (Jnz, 0) {
+        return None;
+    }
+
+    let mut shift = 0;
+    let mut outputs = 0;
+    let (mut b_written, mut c_written) = (false, false);
+
+    for (instruction, operand) in body {
+        let reads_b = match instruction {
+            Bxl | Bxc => true,
+            _ => instruction.has_combo_operand() && *operand == 5,
+        };
+        let reads_c = match instruction {
+            Bxc => true,
+            _ => instruction.has_combo_operand() && *operand == 6,
+        };
+
+        if (reads_b && !b_written) || (reads_c && !c_written) {
+            return None;
+        }
+
+        match instruction {
+            // Only a constant shift of a keeps the iterations independent
+            Adv if *operand <= 3 => shift += *operand,
+            Adv | Jnz => return None,
+            Bst | Bxl | Bxc | Bdv => b_written = true,
+            Cdv => c_written = true,
+            Out => outputs += 1,
+        }
+    }
+
+    (shift > 0 && shift < usize::BITS as usize && outputs > 0)
+        .then_some(Strategy::ShiftLoop { shift, outputs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks the answer by running it and against every smaller a
+    fn assert_minimal(program: &[usize], target: &[usize], a: usize) {
+        let run = |a| Computer::new_with_registers(a, 0, 0).run(program).ok();
+
+        assert_eq!(run(a).as_deref(), Some(target));
+        assert!((0..a).all(|smaller| run(smaller).as_deref() != Some(target)));
+    }
+
+    #[test]
+    fn test_solve_quine() {
+        let program = [0, 3, 5, 4, 3, 0];
+        let solver = TargetSolver::new(&program, &program);
+
+        assert_eq!(
+            solver.strategy(),
+            Strategy::ShiftLoop {
+                shift: 3,
+                outputs: 1
+            }
+        );
+        assert_eq!(solver.solve(), Ok(117440));
+    }
+
+    #[test]
+    fn test_solve_other_targets() {
+        // b = a & 7, b ^= 5, c = a >> b, b ^= c, out b, a >>= 2
+        let program = [2, 4, 1, 5, 7, 5, 4, 0, 5, 5, 0, 2, 3, 0];
+        let solver = TargetSolver::new(&program, &[]);
+
+        assert_eq!(
+            solver.strategy(),
+            Strategy::ShiftLoop {
+                shift: 2,
+                outputs: 1
+            }
+        );
+
+        for seed in [1, 37, 1234, 4321] {
+            let target = Computer::new_with_registers(seed, 0, 0)
+                .run(&program)
+                .unwrap();
+            let a = TargetSolver::new(&program, &target).solve().unwrap();
+            assert!(a <= seed);
+            assert_minimal(&program, &target, a);
+        }
+    }
+
+    #[test]
+    fn test_solve_two_outputs_per_iteration() {
+        // out a, adv 1, out a, adv 1, jnz 0
+        let program = [5, 4, 0, 1, 5, 4, 0, 1, 3, 0];
+        let target = [6, 3, 1, 0];
+        let solver = TargetSolver::new(&program, &target);
+
+        assert_eq!(
+            solver.strategy(),
+            Strategy::ShiftLoop {
+                shift: 2,
+                outputs: 2
+            }
+        );
+        let a = solver.solve().unwrap();
+        assert_minimal(&program, &target, a);
+
+        assert_eq!(
+            TargetSolver::new(&program, &[6, 3, 1]).solve(),
+            Err(SolveError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_solve_no_solution() {
+        let program = [0, 3, 5, 4, 3, 0];
+
+        assert_eq!(
+            TargetSolver::new(&program, &[]).solve(),
+            Err(SolveError::NoSolution)
+        );
+        // The last iteration sees a below 8, a = 7 would be needed after a final shift
+        assert_eq!(
+            TargetSolver::new(&program, &[0, 0, 7]).solve(),
+            Err(SolveError::NoSolution)
+        );
+        assert_eq!(
+            TargetSolver::new(&[0, 3, 5], &[0]).solve(),
+            Err(SolveError::InvalidProgram(
+                ComputerError::OddLengthProgram { length: 3 }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_solve_bit_search() {
+        // b ^= 1 reads b from the previous iteration, so outputs alternate 1, 0, 1, ...
+        let program = [1, 1, 5, 5, 0, 3, 3, 0];
+        let solver = TargetSolver::new(&program, &[1, 0, 1]);
+
+        assert_eq!(solver.strategy(), Strategy::BitSearch);
+        assert_eq!(solver.solve(), Ok(64));
+        // Every output is wrong from the first one, which no a changes
+        assert_eq!(
+            TargetSolver::new(&program, &[0]).solve(),
+            Err(SolveError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn test_solve_bit_search_long_target() {
+        // b = a & 7, b ^= 1, c = a >> b, b ^= 5, b ^= c, out b, a >>= 3, a shift loop
+        let shift_loop = [2, 4, 1, 1, 7, 5, 1, 5, 4, 2, 5, 5, 0, 3, 3, 0];
+        // The same after a leading b ^= c, which reads b and c before writing them
+        let mut program = vec![4, 0];
+        program.extend(shift_loop);
+
+        let seed: usize = 0o7123_4567_0765_4321;
+        let target = Computer::new_with_registers(seed, 0, 0)
+            .run(&shift_loop)
+            .unwrap();
+        assert_eq!(target.len(), 16);
+
+        let solver = TargetSolver::new(&program, &target);
+        assert_eq!(solver.strategy(), Strategy::BitSearch);
+        assert_eq!(
+            solver.solve(),
+            TargetSolver::new(&shift_loop, &target).solve()
+        );
+    }
+
+    #[test]
+    fn test_solve_search_limit() {
+        // a >>= 1 until it is zero, nothing is ever output
+        let program = [0, 1, 3, 0];
+
+        assert_eq!(
+            TargetSolver::new(&program, &[0])
+                .search_limit(1 << 10)
+                .solve(),
+            Err(SolveError::SearchLimitReached { limit: 1 << 10 })
+        );
+    }
+
+    #[test]
+    fn test_solve_with_free_registers() {
+        let program = [1, 1, 5, 5, 0, 3, 3, 0];
+        let solver = TargetSolver::new(&program, &[0, 1]);
+
+        // With b = 0 the first output is always 1
+        assert_eq!(solver.solve_with_free_registers(0..=1), Ok((8, 1, 0)));
+
+        let quine = [0, 3, 5, 4, 3, 0];
+        let solver = TargetSolver::new(&quine, &quine);
+        assert_eq!(solver.solve_with_free_registers(0..=7), Ok((117440, 0, 0)));
+    }
+}
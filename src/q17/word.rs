@@ -0,0 +1,296 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+
+// A register of the computer. Only what the instructions need is required, a shift that does
+// not fit in the width is reported by returning None rather than wrapping
+pub trait Word: Clone + Eq + Ord + Debug + Display {
+    fn from_usize(value: usize) -> Self;
+    fn to_usize(&self) -> Option<usize>;
+    fn is_zero(&self) -> bool;
+    // The value masked with a few low bits
+    fn low_bits(&self, mask: usize) -> usize;
+    fn xor(&self, other: &Self) -> Self;
+    fn shift_right(&self, shift: &Self) -> Option<Self>;
+    // (self << shift) | low, None when set bits would be shifted out
+    fn shift_left_or(&self, shift: usize, low: usize) -> Option<Self>;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                fn from_usize(value: usize) -> Self {
+                    value as $t
+                }
+
+                fn to_usize(&self) -> Option<usize> {
+                    usize::try_from(*self).ok()
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == 0
+                }
+
+                fn low_bits(&self, mask: usize) -> usize {
+                    (*self & mask as $t) as usize
+                }
+
+                fn xor(&self, other: &Self) -> Self {
+                    self ^ other
+                }
+
+                fn shift_right(&self, shift: &Self) -> Option<Self> {
+                    u32::try_from(*shift)
+                        .ok()
+                        .and_then(|shift| self.checked_shr(shift))
+                }
+
+                fn shift_left_or(&self, shift: usize, low: usize) -> Option<Self> {
+                    let shift = u32::try_from(shift).ok().filter(|shift| *shift < <$t>::BITS)?;
+                    (self.leading_zeros() >= shift).then(|| (self << shift) | low as $t)
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(usize, u64, u128);
+
+// Unbounded register, little endian 64 bit limbs without trailing zero limbs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn normalized(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    pub fn bits(&self) -> usize {
+        self.limbs.last().map_or(0, |top| {
+            self.limbs.len() * 64 - top.leading_zeros() as usize
+        })
+    }
+
+    fn shr(&self, shift: usize) -> Self {
+        let (limbs, bits) = (shift / 64, shift % 64);
+        let Some(rest) = self.limbs.get(limbs..) else {
+            return Self::default();
+        };
+
+        let shifted = rest
+            .iter()
+            .enumerate()
+            .map(|(i, limb)| {
+                let high = match bits {
+                    0 => 0,
+                    _ => rest.get(i + 1).map_or(0, |next| next << (64 - bits)),
+                };
+                (limb >> bits) | high
+            })
+            .collect();
+
+        Self::normalized(shifted)
+    }
+
+    fn shl(&self, shift: usize) -> Self {
+        if self.limbs.is_empty() {
+            return Self::default();
+        }
+
+        let (limbs, bits) = (shift / 64, shift % 64);
+        let mut shifted = vec![0; limbs];
+        let mut carry = 0;
+
+        for limb in &self.limbs {
+            shifted.push((limb << bits) | carry);
+            carry = match bits {
+                0 => 0,
+                _ => limb >> (64 - bits),
+            };
+        }
+        shifted.push(carry);
+
+        Self::normalized(shifted)
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        let length = self.limbs.len().max(other.limbs.len());
+        let limbs = (0..length)
+            .map(|i| {
+                f(
+                    self.limbs.get(i).copied().unwrap_or(0),
+                    other.limbs.get(i).copied().unwrap_or(0),
+                )
+            })
+            .collect();
+
+        Self::normalized(limbs)
+    }
+
+    fn div_rem_small(&self, divisor: u64) -> (Self, u64) {
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = 0u128;
+
+        for (i, limb) in self.limbs.iter().enumerate().rev() {
+            let value = (remainder << 64) | *limb as u128;
+            quotient[i] = (value / divisor as u128) as u64;
+            remainder = value % divisor as u128;
+        }
+
+        (Self::normalized(quotient), remainder as u64)
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        Self::normalized(vec![value as u64, (value >> 64) as u64])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 19 decimal digits at a time, the most fitting in a limb
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        let mut chunks = Vec::new();
+        let mut value = self.clone();
+        while !value.limbs.is_empty() {
+            let (quotient, remainder) = value.div_rem_small(CHUNK);
+            chunks.push(remainder);
+            value = quotient;
+        }
+
+        let Some((most_significant, rest)) = chunks.split_last() else {
+            return write!(f, "0");
+        };
+
+        write!(f, "{}", most_significant)?;
+        for chunk in rest.iter().rev() {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Word for BigUint {
+    fn from_usize(value: usize) -> Self {
+        Self::from(value as u128)
+    }
+
+    fn to_usize(&self) -> Option<usize> {
+        match self.limbs.as_slice() {
+            [] => Some(0),
+            [limb] => usize::try_from(*limb).ok(),
+            _ => None,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn low_bits(&self, mask: usize) -> usize {
+        self.limbs.first().map_or(0, |limb| *limb as usize & mask)
+    }
+
+    fn xor(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a ^ b)
+    }
+
+    // Never overflows, shifting past every set bit leaves zero
+    fn shift_right(&self, shift: &Self) -> Option<Self> {
+        Some(
+            shift
+                .to_usize()
+                .map_or_else(Self::default, |shift| self.shr(shift)),
+        )
+    }
+
+    fn shift_left_or(&self, shift: usize, low: usize) -> Option<Self> {
+        Some(
+            self.shl(shift)
+                .zip_with(&Self::from_usize(low), |a, b| a | b),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big_uint_matches_u128() {
+        let values = [
+            0,
+            1,
+            7,
+            u64::MAX as u128,
+            1 << 64,
+            0x1234_5678_9abc_def0_1122_3344_5566,
+        ];
+
+        for value in values {
+            let big = BigUint::from(value);
+            assert_eq!(big.to_string(), value.to_string());
+            assert_eq!(big.bits(), (u128::BITS - value.leading_zeros()) as usize);
+            assert_eq!(big.low_bits(7), (value & 7) as usize);
+
+            for shift in [0, 3, 63, 64, 65, 100] {
+                let expected = value.checked_shr(shift).unwrap();
+                assert_eq!(
+                    big.shift_right(&BigUint::from_usize(shift as usize)),
+                    Some(BigUint::from(expected))
+                );
+            }
+
+            for other in values {
+                assert_eq!(big.xor(&BigUint::from(other)), BigUint::from(value ^ other));
+                assert_eq!(big.cmp(&BigUint::from(other)), value.cmp(&other));
+            }
+        }
+    }
+
+    #[test]
+    fn test_big_uint_grows() {
+        let mut value = BigUint::from_usize(1);
+        for _ in 0..50 {
+            value = value.shift_left_or(3, 5).unwrap();
+        }
+
+        assert_eq!(value.bits(), 151);
+        assert_eq!(value.low_bits(7), 5);
+        assert_eq!(
+            value.shift_right(&BigUint::from_usize(150)),
+            Some(BigUint::from(1))
+        );
+        assert_eq!(
+            BigUint::from(1 << 100).to_string(),
+            "1267650600228229401496703205376"
+        );
+
+        // Fixed widths refuse to lose bits instead
+        assert_eq!(u64::MAX.shift_left_or(1, 0), None);
+        assert_eq!(1u128.shift_left_or(127, 0), Some(1 << 127));
+        assert_eq!(1u64.shift_right(&64), None);
+    }
+}
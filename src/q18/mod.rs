@@ -1,9 +1,10 @@
 use crate::{
     a_star_search::AStarSearch,
-    graph::Graph,
+    graph::{Corridor, Graph, GridGraphBuilder},
     grid::{Grid, Tile},
+    lpa_star::LpaStar,
+    union_find::UnionFind,
 };
-use hashbrown::HashSet;
 use rayon::prelude::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -17,11 +18,13 @@ impl Tile for Q18Tile {
         matches!(self, Self::Empty)
     }
 
-    fn cost_from<V>(&self, _other: V) -> f64
+    type Cost = usize;
+
+    fn cost_from<V>(&self, _other: V) -> usize
     where
         V: Tile,
     {
-        1.0
+        1
     }
 }
 
@@ -42,56 +45,30 @@ fn parse_input(input: &str) -> Vec<(usize, usize)> {
         .collect()
 }
 
-fn construct_graph(
+fn construct_grid(
     falling_bytes: &[(usize, usize)],
     bytes_to_fall: usize,
     (rows, cols): (usize, usize),
-) -> Graph<(usize, usize), usize> {
-    let mut graph = Graph::new();
-    let bytes_to_fall = falling_bytes.len().min(bytes_to_fall);
-    let bytes = &falling_bytes[..bytes_to_fall];
-    let mut byte_positions = HashSet::with_capacity(bytes.len());
-    for (i, j) in bytes {
-        byte_positions.insert((*i, *j));
-    }
-    println!("{:?}", byte_positions);
-
-    for i in 0..rows {
-        for j in 0..cols {
-            if byte_positions.contains(&(i, j)) {
-                continue;
-            }
-
-            for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
-                let new_i = i as isize + dx;
-                let new_j = j as isize + dy;
-
-                if new_i < 0 || new_j < 0 {
-                    continue;
-                }
-
-                let new_i = new_i as usize;
-                let new_j = new_j as usize;
-
-                if new_i >= rows || new_j >= cols {
-                    continue;
-                }
-
-                if byte_positions.contains(&(new_i, new_j)) {
-                    continue;
-                }
-
-                graph.add_edge((i, j), (new_i, new_j), 1);
-            }
-        }
+) -> Grid<Q18Tile> {
+    let mut grid = Grid::with_capacity_and_default(rows, cols, Q18Tile::Empty);
+    for (i, j) in falling_bytes.iter().take(bytes_to_fall) {
+        grid[(*i, *j)] = Q18Tile::Obstacle;
     }
+    grid
+}
 
-    graph
+fn construct_graph(
+    falling_bytes: &[(usize, usize)],
+    bytes_to_fall: usize,
+    dim: (usize, usize),
+) -> Graph<(usize, usize), Corridor<usize>> {
+    let grid = construct_grid(falling_bytes, bytes_to_fall, dim);
+    GridGraphBuilder::new(&grid).build()
 }
 
-fn heuristic(source: (usize, usize), target: (usize, usize)) -> f64 {
-    let dx = (source.0 as isize - target.0 as isize).abs() as f64;
-    let dy = (source.1 as isize - target.1 as isize).abs() as f64;
+fn heuristic(source: (usize, usize), target: (usize, usize)) -> usize {
+    let dx = source.0.abs_diff(target.0);
+    let dy = source.1.abs_diff(target.1);
     dy + dx
 }
 
@@ -105,8 +82,8 @@ pub fn get_bytes_shortest_path_length(
     let falling_bytes = parse_input(input);
     let graph = construct_graph(&falling_bytes, bytes_to_fall, dim);
 
-    let path = graph.a_star_search(start, end, heuristic);
-    path.unwrap().len() - 1
+    let result = graph.a_star_search_result(start, end, heuristic);
+    result.unwrap().cost
 }
 
 pub fn get_bytes_shortest_path_length_grid(
@@ -117,14 +94,10 @@ pub fn get_bytes_shortest_path_length_grid(
     end: (usize, usize),
 ) -> usize {
     let falling_bytes = parse_input(input);
+    let grid = construct_grid(&falling_bytes, bytes_to_fall, dim);
 
-    let mut grid = Grid::with_capacity_and_default(dim.0, dim.1, Q18Tile::Empty);
-    for (i, j) in falling_bytes.iter().take(bytes_to_fall) {
-        grid[(*i, *j)] = Q18Tile::Obstacle;
-    }
-
-    let path = grid.a_star_search(start, end, heuristic);
-    path.unwrap().len() - 1
+    let result = grid.a_star_search_result(start, end, heuristic);
+    result.unwrap().cost
 }
 
 pub fn find_minimum_bytes_to_fall(
@@ -135,29 +108,7 @@ pub fn find_minimum_bytes_to_fall(
 ) -> Option<(usize, usize)> {
     let falling_bytes = parse_input(input);
 
-    let mut graph = Graph::new();
-
-    for i in 0..rows {
-        for j in 0..cols {
-            for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
-                let new_i = i as isize + dx;
-                let new_j = j as isize + dy;
-
-                if new_i < 0 || new_j < 0 {
-                    continue;
-                }
-
-                let new_i = new_i as usize;
-                let new_j = new_j as usize;
-
-                if new_i >= rows || new_j >= cols {
-                    continue;
-                }
-
-                graph.add_edge((i, j), (new_i, new_j), 1);
-            }
-        }
-    }
+    let mut graph = construct_graph(&[], 0, (rows, cols));
 
     for byte_position in falling_bytes {
         graph.remove_vertex(byte_position);
@@ -182,13 +133,9 @@ pub fn find_minimum_bytes_to_fall_parallel(
     (0..falling_bytes.len())
         .into_par_iter()
         .find_first(|idx| {
-            let mut grid = Grid::with_capacity_and_default(rows, cols, Q18Tile::Empty);
-            for (i, j) in falling_bytes.iter().take(*idx) {
-                grid[(*i, *j)] = Q18Tile::Obstacle;
-            }
+            let grid = construct_grid(&falling_bytes, *idx, (rows, cols));
 
-            let path = grid.a_star_search(start, end, heuristic);
-            path.is_none()
+            grid.jump_point_search(start, end).is_none()
         })
         .map(|byte_position| {
             let byte_position = falling_bytes[byte_position - 1];
@@ -196,9 +143,82 @@ pub fn find_minimum_bytes_to_fall_parallel(
         })
 }
 
+// Keeps a single search alive and repairs it as every byte falls instead of starting over
+pub fn find_minimum_bytes_to_fall_incremental(
+    input: &str,
+    dim: (usize, usize),
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<(usize, usize)> {
+    let falling_bytes = parse_input(input);
+    let grid = construct_grid(&[], 0, dim);
+
+    let mut search = LpaStar::new(&grid, start, end, heuristic);
+
+    falling_bytes
+        .into_iter()
+        .find(|byte_position| search.add_obstacle(*byte_position).is_none())
+        .map(|byte_position| (byte_position.1, byte_position.0)) // invert the coordinates
+}
+
+// Starts from the fully fallen memory space and removes bytes in reverse, the first
+// byte whose removal reconnects start and end is the one that first cut them off
+pub fn find_minimum_bytes_to_fall_union_find(
+    input: &str,
+    (rows, cols): (usize, usize),
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<(usize, usize)> {
+    let falling_bytes = parse_input(input);
+
+    // Bytes can land on the same position more than once
+    let mut fallen = Grid::with_capacity_and_default(rows, cols, 0usize);
+    for position in &falling_bytes {
+        fallen[*position] += 1;
+    }
+
+    let index = |(i, j): (usize, usize)| i * cols + j;
+    let mut union_find = UnionFind::new(rows * cols);
+
+    let join_free_neighbours = |union_find: &mut UnionFind, fallen: &Grid<usize>, position| {
+        for neighbour in fallen.adjacent_indices(position, false) {
+            if fallen[neighbour] == 0 {
+                union_find.union(index(position), index(neighbour));
+            }
+        }
+    };
+
+    for i in 0..rows {
+        for j in 0..cols {
+            if fallen[(i, j)] == 0 {
+                join_free_neighbours(&mut union_find, &fallen, (i, j));
+            }
+        }
+    }
+
+    if union_find.connected(index(start), index(end)) {
+        return None;
+    }
+
+    for position in falling_bytes.iter().rev() {
+        fallen[*position] -= 1;
+        if fallen[*position] > 0 {
+            continue;
+        }
+
+        join_free_neighbours(&mut union_find, &fallen, *position);
+
+        if union_find.connected(index(start), index(end)) {
+            return Some((position.1, position.0)); // invert the coordinates
+        }
+    }
+
+    None
+}
+
 #[allow(dead_code)]
 fn display_path_on_grid(
-    graph: &Graph<(usize, usize), usize>,
+    graph: &Graph<(usize, usize), Corridor<usize>>,
     (rows, cols): (usize, usize),
     path: &[(usize, usize)],
 ) {
@@ -265,6 +285,26 @@ mod tests {
         assert_eq!(result, 22);
     }
 
+    #[test]
+    fn test_jump_point_search_matches_a_star() {
+        let falling_bytes = parse_input(TEST_INPUT);
+
+        for bytes_to_fall in 0..=falling_bytes.len() {
+            let grid = construct_grid(&falling_bytes, bytes_to_fall, (7, 7));
+
+            let a_star = grid.a_star_search_result((0, 0), (6, 6), heuristic);
+            let jps = grid.jump_point_search((0, 0), (6, 6));
+
+            assert_eq!(
+                jps.as_ref().map(|r| r.cost),
+                a_star.as_ref().map(|r| r.cost)
+            );
+            if let (Some(jps), Some(a_star)) = (jps, a_star) {
+                assert!(jps.expanded <= a_star.expanded);
+            }
+        }
+    }
+
     #[test]
     fn test_find_minimum_bytes_to_fall() {
         let result = find_minimum_bytes_to_fall(TEST_INPUT, (7, 7), (0, 0), (6, 6));
@@ -276,4 +316,16 @@ mod tests {
         let result = find_minimum_bytes_to_fall_parallel(TEST_INPUT, (7, 7), (0, 0), (6, 6));
         assert_eq!(result, Some((6, 1)));
     }
+
+    #[test]
+    fn test_find_minimum_bytes_to_fall_incremental() {
+        let result = find_minimum_bytes_to_fall_incremental(TEST_INPUT, (7, 7), (0, 0), (6, 6));
+        assert_eq!(result, Some((6, 1)));
+    }
+
+    #[test]
+    fn test_find_minimum_bytes_to_fall_union_find() {
+        let result = find_minimum_bytes_to_fall_union_find(TEST_INPUT, (7, 7), (0, 0), (6, 6));
+        assert_eq!(result, Some((6, 1)));
+    }
 }
@@ -1,5 +1,3 @@
-use hashbrown::HashMap;
-
 use crate::a_star_search::AStarSearch;
 use crate::grid::{Grid, Tile};
 use rayon::prelude::*;
@@ -14,11 +12,13 @@ impl Tile for RaceTrack {
     fn traversable(&self) -> bool {
         matches!(self, Self::Empty)
     }
-    fn cost_from<V>(&self, _other: V) -> f64
+    type Cost = u32;
+
+    fn cost_from<V>(&self, _other: V) -> u32
     where
         V: Tile,
     {
-        1.0
+        1
     }
 }
 
@@ -70,7 +70,7 @@ fn get_optimum_path(
     start: (usize, usize),
     end: (usize, usize),
 ) -> Vec<(usize, usize)> {
-    grid.a_star_search(start, end, manhattan_distance::<f64>)
+    grid.a_star_search(start, end, manhattan_distance::<u32>)
         .expect("No path found")
 }
 
@@ -146,6 +146,7 @@ pub fn get_total_number_of_cheats(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::GridGraphBuilder;
     use rstest::rstest;
 
     const TEST_INPUT: &str = "
@@ -165,6 +166,24 @@ mod tests {
 #...#...#...###
 ###############";
 
+    #[test]
+    fn test_bidirectional_a_star_matches_optimum_path() {
+        let (grid, start, end) = parse_input(TEST_INPUT);
+        let graph = GridGraphBuilder::new(&grid)
+            .contract_corridors(true)
+            .keep(start)
+            .keep(end)
+            .build();
+
+        let result = graph
+            .bidirectional_a_star(start, end, manhattan_distance::<u32>)
+            .unwrap();
+        let path = graph.expand_path(&result.path);
+
+        assert_eq!(path, get_optimum_path(&grid, start, end));
+        assert_eq!(result.cost as usize, path.len() - 1);
+    }
+
     #[rstest]
     #[case(65, 0, 2)]
     #[case(64, 1, 2)]
@@ -1,4 +1,4 @@
-fn parse_input(input: &str) -> Vec<[u32; 3]> {
+pub fn parse_input(input: &str) -> Vec<[u32; 3]> {
     input
         .trim()
         .lines()
@@ -17,3 +17,17 @@ fn parse_input(input: &str) -> Vec<[u32; 3]> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let input = "029A\n980A\n179A\n456A\n379A\n";
+        assert_eq!(
+            parse_input(input),
+            [[0, 2, 9], [9, 8, 0], [1, 7, 9], [4, 5, 6], [3, 7, 9]]
+        );
+    }
+}
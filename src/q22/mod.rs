@@ -1,6 +1,3 @@
-use std::hash::Hash;
-use std::ops::Add;
-
 use hashbrown::HashMap;
 
 fn parse_input(input: &str) -> Vec<u128> {
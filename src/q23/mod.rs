@@ -31,11 +31,7 @@ where
 {
     let graph = parse_input(input);
 
-    graph
-        .k_cliques(3)
-        .iter()
-        .filter(|clique| predicate(clique.as_slice()))
-        .count()
+    graph.count_k_cliques_where(3, predicate)
 }
 
 pub fn get_largest_clique(input: &str) -> HashSet<&str> {
@@ -1,69 +1,116 @@
+use std::fmt::Display;
 use std::str::FromStr;
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
 use super::op::{Op, SimpleGate};
 
+// The naming of a ripple carry adder. The carry of bit 0 is the plain AND, so it comes first and
+// wins over the generic rule
+pub const ADDER_ALIAS_RULES: &str = "\
+x00 AND y00 -> CARRY00
+x{n} AND y{n} -> AND{n}
+x{n} XOR y{n} -> XOR{n}
+XOR{n} AND CARRY{n-1} -> CARRY_INTERMEDIATE{n}
+AND{n} OR CARRY_INTERMEDIATE{n} -> CARRY{n}
+";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Numeric {
-    N,
-    Nm1,
+pub enum RuleError {
+    // Lines are numbered from 1
+    Syntax { line: usize, text: String },
+    UnknownOp { line: usize, op: String },
+    InvalidPattern { line: usize, pattern: String },
+    // The alias needs a bit number that neither input provides
+    UnboundIndex { line: usize },
 }
 
-#[derive(Debug, Clone)]
-pub struct AliasDescriptor {
-    pub prefix: String,
-    pub numeric: Numeric,
+impl Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax { line, text } => {
+                write!(f, "line {}: expected 'a OP b -> c', got '{}'", line, text)
+            }
+            Self::UnknownOp { line, op } => write!(f, "line {}: unknown operator {}", line, op),
+            Self::InvalidPattern { line, pattern } => {
+                write!(f, "line {}: invalid wire pattern {}", line, pattern)
+            }
+            Self::UnboundIndex { line } => {
+                write!(f, "line {}: the alias uses {{n}} but no input does", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// Either an exact wire name, or a prefix followed by a two digit bit number `{n}` shifted by an
+// offset, as in `CARRY{n-1}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasDescriptor {
+    Exact(String),
+    Numbered { prefix: String, offset: isize },
 }
 
 impl FromStr for AliasDescriptor {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.trim();
+        let is_name = |s: &str| s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
 
-        let (prefix, numeric) = s.split_once('(').ok_or(())?;
+        let Some((prefix, rest)) = s.split_once('{') else {
+            return (!s.is_empty() && is_name(s))
+                .then(|| Self::Exact(s.to_string()))
+                .ok_or(());
+        };
 
-        let numeric = match numeric {
-            "N)" => Numeric::N,
-            "N-1)" => Numeric::Nm1,
+        let offset = match rest.strip_prefix('n').and_then(|s| s.strip_suffix('}')) {
+            Some("") => 0,
+            Some(offset) if offset.starts_with('+') => offset[1..].parse().map_err(|_| ())?,
+            Some(offset) if offset.starts_with('-') => offset.parse().map_err(|_| ())?,
             _ => return Err(()),
         };
 
-        Ok(Self {
+        if !is_name(prefix) {
+            return Err(());
+        }
+
+        Ok(Self::Numbered {
             prefix: prefix.to_string(),
-            numeric,
+            offset,
         })
     }
 }
 
-impl From<&str> for AliasDescriptor {
-    fn from(s: &str) -> Self {
-        s.parse().unwrap()
-    }
-}
-
 impl AliasDescriptor {
-    fn applies(&self, name: &str) -> Option<usize> {
-        name.strip_prefix(&self.prefix)
-            .and_then(|s| s.parse().ok())
-            .map(
-                |n: usize| {
-                    if self.numeric == Numeric::N {
-                        n
-                    } else {
-                        n + 1
-                    }
-                },
-            )
+    // Some(None) for an exact match, Some(Some(n)) with the bit number of a numbered one
+    fn applies(&self, name: &str) -> Option<Option<usize>> {
+        match self {
+            Self::Exact(wire) => (wire == name).then_some(None),
+            Self::Numbered { prefix, offset } => {
+                let digits = name.strip_prefix(prefix.as_str())?;
+                if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+
+                let n = digits.parse::<isize>().ok()? - offset;
+                Some(Some(usize::try_from(n).ok()?))
+            }
+        }
     }
 
-    fn generate(&self, n: usize) -> String {
-        let n = if self.numeric == Numeric::N { n } else { n - 1 };
-        format!("{}{:0>2}", self.prefix, n)
+    fn generate(&self, n: Option<usize>) -> Option<String> {
+        match self {
+            Self::Exact(wire) => Some(wire.clone()),
+            Self::Numbered { prefix, offset } => {
+                let n = usize::try_from(n? as isize + offset).ok()?;
+                Some(format!("{}{:0>2}", prefix, n))
+            }
+        }
     }
 }
 
+// `a OP b -> alias`, the inputs match either way round and must agree on the bit number
 #[derive(Debug, Clone)]
 pub struct Alias {
     alias_1: AliasDescriptor,
@@ -73,65 +120,368 @@ pub struct Alias {
 }
 
 impl Alias {
-    pub fn new<A1, A2, A3>(alias_1: A1, op: Op, alias_2: A2, alias_generator: A3) -> Self
-    where
-        A1: Into<AliasDescriptor>,
-        A2: Into<AliasDescriptor>,
-        A3: Into<AliasDescriptor>,
-    {
-        Self {
-            alias_1: alias_1.into(),
+    fn parse(line: usize, text: &str) -> Result<Self, RuleError> {
+        let syntax = || RuleError::Syntax {
+            line,
+            text: text.to_string(),
+        };
+        let pattern = |pattern: &str| {
+            pattern
+                .parse::<AliasDescriptor>()
+                .map_err(|_| RuleError::InvalidPattern {
+                    line,
+                    pattern: pattern.to_string(),
+                })
+        };
+
+        let (gate, alias) = text.split_once("->").ok_or_else(syntax)?;
+        let [a, op, b] = gate.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(syntax());
+        };
+        let [alias] = alias.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(syntax());
+        };
+
+        let op = op.parse().map_err(|_| RuleError::UnknownOp {
+            line,
+            op: op.to_string(),
+        })?;
+
+        let rule = Self {
+            alias_1: pattern(a)?,
             op,
-            alias_2: alias_2.into(),
-            alias_generator: alias_generator.into(),
+            alias_2: pattern(b)?,
+            alias_generator: pattern(alias)?,
+        };
+
+        let numbered =
+            |descriptor: &AliasDescriptor| matches!(descriptor, AliasDescriptor::Numbered { .. });
+        if numbered(&rule.alias_generator) && !numbered(&rule.alias_1) && !numbered(&rule.alias_2) {
+            return Err(RuleError::UnboundIndex { line });
+        }
+
+        Ok(rule)
+    }
+
+    fn matches(&self, a: &str, b: &str) -> Option<Option<usize>> {
+        let n_1 = self.alias_1.applies(a)?;
+        let n_2 = self.alias_2.applies(b)?;
+
+        match (n_1, n_2) {
+            (Some(n_1), Some(n_2)) if n_1 != n_2 => None,
+            _ => Some(n_1.or(n_2)),
         }
     }
-}
 
-impl Alias {
     pub fn try_generate_alias(&self, gate: &SimpleGate) -> Option<String> {
         if gate.op != self.op {
             return None;
         }
 
-        let a_1 = self.alias_1.applies(&gate.a);
-        let a_2 = self.alias_2.applies(&gate.a);
+        let n = self
+            .matches(&gate.a, &gate.b)
+            .or_else(|| self.matches(&gate.b, &gate.a))?;
 
-        let b_1 = self.alias_1.applies(&gate.b);
-        let b_2 = self.alias_2.applies(&gate.b);
+        self.alias_generator.generate(n)
+    }
+}
 
-        if !((a_1.is_some() && b_2.is_some()) || (a_2.is_some() && b_1.is_some())) {
-            return None;
-        }
+// An ordered list of rules, one per line. Blank lines and everything after a `#` are ignored
+// A wire that kept its name because its alias was already taken by another wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasConflict {
+    pub wire: String,
+    pub alias: String,
+}
 
-        let a = if a_1.is_some() { a_1 } else { a_2 }.unwrap();
-        let b = if b_1.is_some() { b_1 } else { b_2 }.unwrap();
+impl Display for AliasConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot rename {} to {}, the name is already taken",
+            self.wire, self.alias
+        )
+    }
+}
 
-        if a != b {
-            return None;
-        }
+#[derive(Debug, Clone, Default)]
+pub struct Renaming {
+    // The name each renamed wire had before, mapped to its alias
+    pub wires: HashMap<String, String>,
+    pub conflicts: Vec<AliasConflict>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AliasRules {
+    rules: Vec<Alias>,
+}
 
-        Some(self.alias_generator.generate(a))
+impl FromStr for AliasRules {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rules = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.split('#').next().unwrap().trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(line, text)| Alias::parse(line, text))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { rules })
     }
+}
 
-    pub fn alias(&self, gates: &mut [SimpleGate], renamed_wires: &mut HashMap<String, String>) {
-        for g in gates.iter() {
-            if let Some(new_name) = self.try_generate_alias(g) {
-                renamed_wires.insert(g.c.clone(), new_name.clone());
-            }
-        }
+impl AliasRules {
+    pub fn adder() -> Self {
+        ADDER_ALIAS_RULES.parse().unwrap()
+    }
 
-        for g in gates {
-            if let Some(new_a) = renamed_wires.get(&g.a) {
-                g.a = new_a.clone();
-            }
-            if let Some(new_b) = renamed_wires.get(&g.b) {
-                g.b = new_b.clone();
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Renames the gate outputs in place until no rule finds anything new, so aliases can build
+    // on each other along a chain. The first matching rule names a wire and a wire is named only
+    // once. A wire whose alias is already the name of another wire keeps its name and is reported
+    // as a conflict, since renaming it would merge the two wires
+    pub fn apply(&self, gates: &mut [SimpleGate]) -> Renaming {
+        let mut renaming = Renaming::default();
+        let mut aliases = HashSet::new();
+        let mut names = gates
+            .iter()
+            .flat_map(|gate| [&gate.a, &gate.b, &gate.c])
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        loop {
+            let mut renamed = None;
+
+            for gate in gates.iter() {
+                if aliases.contains(&gate.c) {
+                    continue;
+                }
+
+                let Some(alias) = self
+                    .rules
+                    .iter()
+                    .find_map(|rule| rule.try_generate_alias(gate))
+                else {
+                    continue;
+                };
+
+                if alias != gate.c && names.contains(&alias) {
+                    let conflict = AliasConflict {
+                        wire: gate.c.clone(),
+                        alias,
+                    };
+                    if !renaming.conflicts.contains(&conflict) {
+                        renaming.conflicts.push(conflict);
+                    }
+                    continue;
+                }
+
+                renamed = Some((gate.c.clone(), alias));
+                break;
             }
 
-            if let Some(new_c) = renamed_wires.get(&g.c) {
-                g.c = new_c.clone();
+            let Some((wire, alias)) = renamed else {
+                break;
+            };
+
+            for gate in gates.iter_mut() {
+                for name in [&mut gate.a, &mut gate.b, &mut gate.c] {
+                    if *name == wire {
+                        *name = alias.clone();
+                    }
+                }
             }
+
+            // An alias freed up by a later rename no longer conflicts
+            renaming.conflicts.retain(|conflict| conflict.wire != wire);
+            names.remove(&wire);
+            names.insert(alias.clone());
+            aliases.insert(alias.clone());
+            renaming.wires.insert(wire, alias);
         }
+
+        renaming
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = "
+# Comparator
+x{n} XOR y{n} -> DIFF{n}   # differs at bit n
+
+DIFF{n} OR ANY{n+1} -> ANY{n}
+"
+        .parse::<AliasRules>()
+        .unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(AliasRules::adder().len(), 5);
+
+        let errors = [
+            (
+                "x{n} XOR y{n}",
+                RuleError::Syntax {
+                    line: 1,
+                    text: "x{n} XOR y{n}".to_string(),
+                },
+            ),
+            (
+                "x{n} NAND y{n} -> A{n}",
+                RuleError::UnknownOp {
+                    line: 1,
+                    op: "NAND".to_string(),
+                },
+            ),
+            (
+                "\nx{m} AND y{n} -> A{n}",
+                RuleError::InvalidPattern {
+                    line: 2,
+                    pattern: "x{m}".to_string(),
+                },
+            ),
+            (
+                "x{n-} AND y{n} -> A{n}",
+                RuleError::InvalidPattern {
+                    line: 1,
+                    pattern: "x{n-}".to_string(),
+                },
+            ),
+            ("x00 AND y00 -> A{n}", RuleError::UnboundIndex { line: 1 }),
+        ];
+
+        for (text, error) in errors {
+            assert_eq!(text.parse::<AliasRules>().err(), Some(error));
+        }
+    }
+
+    #[test]
+    fn test_descriptor() {
+        let carry = "CARRY{n-1}".parse::<AliasDescriptor>().unwrap();
+        assert_eq!(carry.applies("CARRY04"), Some(Some(5)));
+        assert_eq!(carry.applies("CARRY_INTERMEDIATE04"), None);
+        assert_eq!(carry.generate(Some(5)), Some("CARRY04".to_string()));
+        assert_eq!(carry.generate(Some(0)), None);
+
+        let next = "ANY{n+1}".parse::<AliasDescriptor>().unwrap();
+        assert_eq!(next.applies("ANY00"), None);
+        assert_eq!(next.applies("ANY10"), Some(Some(9)));
+    }
+
+    #[test]
+    fn test_apply_adder_to_fixpoint() {
+        let mut gates = vec![
+            SimpleGate::new("y00", "x00", Op::And, "aaa"),
+            SimpleGate::new("x00", "y00", Op::Xor, "z00"),
+            SimpleGate::new("x01", "y01", Op::Xor, "bbb"),
+            SimpleGate::new("x01", "y01", Op::And, "ccc"),
+            SimpleGate::new("bbb", "aaa", Op::Xor, "z01"),
+            SimpleGate::new("aaa", "bbb", Op::And, "ddd"),
+            SimpleGate::new("ccc", "ddd", Op::Or, "eee"),
+            SimpleGate::new("x02", "y02", Op::Xor, "fff"),
+            SimpleGate::new("x02", "y02", Op::And, "ggg"),
+            SimpleGate::new("fff", "eee", Op::Xor, "z02"),
+            SimpleGate::new("eee", "fff", Op::And, "hhh"),
+            SimpleGate::new("ggg", "hhh", Op::Or, "z03"),
+        ];
+
+        let renaming = AliasRules::adder().apply(&mut gates);
+        let renamed_wires = renaming.wires;
+        assert!(renaming.conflicts.is_empty());
+        let expected = [
+            ("aaa", "CARRY00"),
+            ("z00", "XOR00"),
+            ("bbb", "XOR01"),
+            ("ccc", "AND01"),
+            ("ddd", "CARRY_INTERMEDIATE01"),
+            ("eee", "CARRY01"),
+            ("fff", "XOR02"),
+            ("ggg", "AND02"),
+            ("hhh", "CARRY_INTERMEDIATE02"),
+            ("z03", "CARRY02"),
+        ];
+
+        assert_eq!(renamed_wires.len(), expected.len());
+        for (wire, alias) in expected {
+            assert_eq!(renamed_wires[wire], alias);
+        }
+
+        assert_eq!(
+            gates[10].to_string(),
+            "CARRY01 AND XOR02 -> CARRY_INTERMEDIATE02"
+        );
+    }
+
+    #[test]
+    fn test_apply_comparator() {
+        // Whether x and y differ at or above each bit, chained from the top
+        let rules =
+            "x{n} XOR y{n} -> DIFF{n}\nDIFF{n} OR ANY{n+1} -> ANY{n}\nDIFF02 OR DIFF02 -> ANY02"
+                .parse::<AliasRules>()
+                .unwrap();
+
+        let mut gates = vec![
+            SimpleGate::new("x00", "y00", Op::Xor, "aaa"),
+            SimpleGate::new("x01", "y01", Op::Xor, "bbb"),
+            SimpleGate::new("x02", "y02", Op::Xor, "ccc"),
+            SimpleGate::new("ccc", "ccc", Op::Or, "ddd"),
+            SimpleGate::new("eee", "aaa", Op::Or, "z00"),
+            SimpleGate::new("bbb", "ddd", Op::Or, "eee"),
+        ];
+
+        let renamed_wires = rules.apply(&mut gates).wires;
+        assert_eq!(renamed_wires["ddd"], "ANY02");
+        assert_eq!(renamed_wires["eee"], "ANY01");
+        assert_eq!(renamed_wires["z00"], "ANY00");
+    }
+
+    #[test]
+    fn test_apply_conflicting_aliases() {
+        // A duplicated half adder names two wires XOR01, and the rule output z02 already exists
+        let rules = "x{n} XOR y{n} -> XOR{n}\nx{n} AND y{n} -> z02"
+            .parse::<AliasRules>()
+            .unwrap();
+
+        let mut gates = vec![
+            SimpleGate::new("x01", "y01", Op::Xor, "aaa"),
+            SimpleGate::new("y01", "x01", Op::Xor, "bbb"),
+            SimpleGate::new("aaa", "bbb", Op::Or, "z01"),
+            SimpleGate::new("x01", "y01", Op::And, "ccc"),
+            SimpleGate::new("ccc", "aaa", Op::Or, "z02"),
+        ];
+
+        let renaming = rules.apply(&mut gates);
+        assert_eq!(renaming.wires.len(), 1);
+        assert_eq!(renaming.wires["aaa"], "XOR01");
+        assert_eq!(
+            renaming.conflicts,
+            [
+                AliasConflict {
+                    wire: "bbb".to_string(),
+                    alias: "XOR01".to_string(),
+                },
+                AliasConflict {
+                    wire: "ccc".to_string(),
+                    alias: "z02".to_string(),
+                },
+            ]
+        );
+        assert_eq!(gates[2].to_string(), "XOR01 OR bbb -> z01");
+        assert_eq!(
+            renaming.conflicts[0].to_string(),
+            "cannot rename bbb to XOR01, the name is already taken"
+        );
     }
 }
@@ -0,0 +1,407 @@
+use std::fmt::Display;
+
+use hashbrown::HashMap;
+
+use super::op::{Op, SimpleGate};
+use super::simulator::{SimulationError, Simulator};
+
+pub const DEFAULT_EXHAUSTIVE_BITS: usize = 16;
+pub const DEFAULT_SAMPLES: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    // Swapping outputs can close a loop or give a wire two drivers
+    Simulation(SimulationError),
+    UndrivenWire { wire: String },
+    TooWide { bits: usize },
+}
+
+impl Display for CircuitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Simulation(error) => write!(f, "{}", error),
+            Self::UndrivenWire { wire } => write!(f, "wire {} is never driven", wire),
+            Self::TooWide { bits } => write!(f, "{} bits do not fit in 64", bits),
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Reference {
+    Add,
+    And,
+    Or,
+    Xor,
+    Custom(fn(u64, u64) -> u64),
+}
+
+impl Reference {
+    pub fn evaluate(&self, x: u64, y: u64) -> u64 {
+        match self {
+            Self::Add => x.wrapping_add(y),
+            Self::And => x & y,
+            Self::Or => x | y,
+            Self::Xor => x ^ y,
+            Self::Custom(f) => f(x, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    pub x: u64,
+    pub y: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl Counterexample {
+    pub fn lowest_failing_bit(&self) -> usize {
+        (self.expected ^ self.actual).trailing_zeros() as usize
+    }
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "x={} y={} expected z={} actual z={}, lowest failing bit {}",
+            self.x,
+            self.y,
+            self.expected,
+            self.actual,
+            self.lowest_failing_bit()
+        )
+    }
+}
+
+// The simulator's gate order, where every input is computed before it is read, over wire indices
+pub struct Circuit {
+    gates: Vec<(usize, usize, Op, usize)>,
+    wires: usize,
+    // (bit, wire index) pairs, the bit is the number in the wire name
+    x: Vec<(usize, usize)>,
+    y: Vec<(usize, usize)>,
+    z: Vec<(usize, usize)>,
+}
+
+fn bit_wires(indices: &HashMap<&str, usize>, prefix: char) -> Vec<(usize, usize)> {
+    let mut wires = indices
+        .iter()
+        .filter_map(|(wire, index)| {
+            let bit = wire.strip_prefix(prefix)?.parse::<usize>().ok()?;
+            Some((bit, *index))
+        })
+        .collect::<Vec<_>>();
+
+    wires.sort();
+    wires
+}
+
+// Bits missing from the middle still count towards the width
+fn width(wires: &[(usize, usize)]) -> usize {
+    wires.last().map_or(0, |(bit, _)| bit + 1)
+}
+
+impl Circuit {
+    pub fn new(gates: &[SimpleGate]) -> Result<Self, CircuitError> {
+        let simulator = Simulator::new(gates).map_err(CircuitError::Simulation)?;
+        let names = simulator.wire_names();
+        let indices = names
+            .iter()
+            .enumerate()
+            .map(|(index, wire)| (wire.as_str(), index))
+            .collect::<HashMap<_, _>>();
+
+        let x = bit_wires(&indices, 'x');
+        let y = bit_wires(&indices, 'y');
+        let z = bit_wires(&indices, 'z');
+        if let Some(bits) = [width(&x), width(&y), width(&z)]
+            .into_iter()
+            .find(|bits| *bits > 64)
+        {
+            return Err(CircuitError::TooWide { bits });
+        }
+
+        // Apart from the inputs, every wire read has to come from a gate
+        let mut inputs = vec![false; names.len()];
+        for (_, index) in x.iter().chain(&y) {
+            inputs[*index] = true;
+        }
+        if let Some(index) = (0..names.len()).find(|i| !inputs[*i] && !simulator.is_driven(*i)) {
+            return Err(CircuitError::UndrivenWire {
+                wire: names[index].clone(),
+            });
+        }
+
+        Ok(Self {
+            gates: simulator.ordered_gates().to_vec(),
+            wires: names.len(),
+            x,
+            y,
+            z,
+        })
+    }
+
+    pub fn input_bits(&self) -> (usize, usize) {
+        (width(&self.x), width(&self.y))
+    }
+
+    pub fn output_bits(&self) -> usize {
+        width(&self.z)
+    }
+
+    pub fn evaluate(&self, x: u64, y: u64) -> u64 {
+        let mut values = vec![false; self.wires];
+
+        for (bit, index) in &self.x {
+            values[*index] = x >> bit & 1 == 1;
+        }
+        for (bit, index) in &self.y {
+            values[*index] = y >> bit & 1 == 1;
+        }
+
+        for (a, b, op, c) in &self.gates {
+            values[*c] = op.evaluate(values[*a], values[*b]);
+        }
+
+        self.z
+            .iter()
+            .fold(0, |z, (bit, index)| z | (values[*index] as u64) << bit)
+    }
+}
+
+// Splitmix64, seeded so failures reproduce
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn mask(bits: usize) -> u64 {
+    u64::MAX.checked_shr(64 - bits as u32).unwrap_or(0)
+}
+
+pub struct EquivalenceChecker<'a> {
+    circuit: &'a Circuit,
+    reference: Reference,
+    exhaustive_bits: usize,
+    samples: usize,
+    seed: u64,
+}
+
+impl<'a> EquivalenceChecker<'a> {
+    pub fn new(circuit: &'a Circuit, reference: Reference) -> Self {
+        Self {
+            circuit,
+            reference,
+            exhaustive_bits: DEFAULT_EXHAUSTIVE_BITS,
+            samples: DEFAULT_SAMPLES,
+            seed: 0,
+        }
+    }
+
+    // Circuits with at most this many input bits in total are checked on every input
+    pub fn exhaustive_bits(mut self, exhaustive_bits: usize) -> Self {
+        self.exhaustive_bits = exhaustive_bits;
+        self
+    }
+
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn failure(&self, x: u64, y: u64) -> Option<Counterexample> {
+        let expected = self.reference.evaluate(x, y) & mask(self.circuit.output_bits());
+        let actual = self.circuit.evaluate(x, y);
+
+        (expected != actual).then_some(Counterexample {
+            x,
+            y,
+            expected,
+            actual,
+        })
+    }
+
+    // Clears set bits of x and y, highest first, for as long as the circuit still fails
+    fn shrink(&self, mut counterexample: Counterexample) -> Counterexample {
+        let (x_bits, y_bits) = self.circuit.input_bits();
+
+        for bit in (0..x_bits.max(y_bits)).rev() {
+            for clear_x in [true, false] {
+                let (x, y) = match clear_x {
+                    true => (counterexample.x & !(1 << bit), counterexample.y),
+                    false => (counterexample.x, counterexample.y & !(1 << bit)),
+                };
+
+                if (x, y) != (counterexample.x, counterexample.y) {
+                    if let Some(smaller) = self.failure(x, y) {
+                        counterexample = smaller;
+                    }
+                }
+            }
+        }
+
+        counterexample
+    }
+
+    // Single bits and carry chains catch most wiring faults in a handful of vectors, random
+    // vectors the rest
+    fn vectors(&self) -> Vec<(u64, u64)> {
+        let (x_bits, y_bits) = self.circuit.input_bits();
+        let (x_mask, y_mask) = (mask(x_bits), mask(y_bits));
+
+        if x_bits + y_bits <= self.exhaustive_bits {
+            return (0..=y_mask)
+                .flat_map(|y| (0..=x_mask).map(move |x| (x, y)))
+                .collect();
+        }
+
+        let mut vectors = Vec::new();
+        for bit in 0..x_bits.max(y_bits) {
+            let single = 1 << bit;
+            vectors.extend([
+                (single & x_mask, 0),
+                (0, single & y_mask),
+                (single & x_mask, single & y_mask),
+                (mask(bit + 1) & x_mask, 1 & y_mask),
+            ]);
+        }
+
+        let mut state = self.seed;
+        for _ in 0..self.samples {
+            let x = next_random(&mut state) & x_mask;
+            let y = next_random(&mut state) & y_mask;
+            vectors.push((x, y));
+        }
+
+        vectors
+    }
+
+    // None when every vector tried agrees with the reference
+    pub fn check(&self) -> Option<Counterexample> {
+        self.vectors()
+            .into_iter()
+            .find_map(|(x, y)| self.failure(x, y))
+            .map(|counterexample| self.shrink(counterexample))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // z = x + y for two bit numbers
+    fn two_bit_adder() -> Vec<SimpleGate> {
+        vec![
+            SimpleGate::new("x00", "y00", Op::Xor, "z00"),
+            SimpleGate::new("x00", "y00", Op::And, "c00"),
+            SimpleGate::new("x01", "y01", Op::Xor, "s01"),
+            SimpleGate::new("x01", "y01", Op::And, "h01"),
+            SimpleGate::new("s01", "c00", Op::Xor, "z01"),
+            SimpleGate::new("s01", "c00", Op::And, "t01"),
+            SimpleGate::new("h01", "t01", Op::Or, "z02"),
+        ]
+    }
+
+    #[test]
+    fn test_exhaustive_check() {
+        let gates = two_bit_adder();
+        let circuit = Circuit::new(&gates).unwrap();
+
+        assert_eq!(circuit.evaluate(3, 3), 6);
+        assert_eq!(
+            EquivalenceChecker::new(&circuit, Reference::Add).check(),
+            None
+        );
+
+        let counterexample = EquivalenceChecker::new(&circuit, Reference::Xor)
+            .check()
+            .unwrap();
+        // 1 + 1 is the smallest input where the carry differs from XOR
+        assert_eq!(
+            counterexample,
+            Counterexample {
+                x: 1,
+                y: 1,
+                expected: 0,
+                actual: 2
+            }
+        );
+        assert_eq!(counterexample.lowest_failing_bit(), 1);
+    }
+
+    #[test]
+    fn test_random_check_shrinks() {
+        // z = x AND y over 40 bits, with bit 23 wired as OR instead
+        let gates = (0..40)
+            .map(|i| {
+                let op = if i == 23 { Op::Or } else { Op::And };
+                SimpleGate::new(
+                    &format!("x{:0>2}", i),
+                    &format!("y{:0>2}", i),
+                    op,
+                    &format!("z{:0>2}", i),
+                )
+            })
+            .collect::<Vec<_>>();
+        let circuit = Circuit::new(&gates).unwrap();
+
+        let checker = EquivalenceChecker::new(&circuit, Reference::And).seed(7);
+        let counterexample = checker.check().unwrap();
+
+        assert_eq!(counterexample.lowest_failing_bit(), 23);
+        assert_eq!((counterexample.x | counterexample.y).count_ones(), 1);
+        assert_eq!(counterexample.x & counterexample.y, 0);
+
+        let custom = Reference::Custom(|x, y| (x & y) | ((x | y) & 1 << 23));
+        assert_eq!(EquivalenceChecker::new(&circuit, custom).check(), None);
+    }
+
+    #[test]
+    fn test_circuit_errors() {
+        let mut gates = two_bit_adder();
+        // z01 feeding t01 feeding z02 is fine, but t01 feeding z01 back closes a loop
+        gates[4] = SimpleGate::new("s01", "t01", Op::Xor, "z01");
+        gates[5] = SimpleGate::new("s01", "z01", Op::And, "t01");
+        assert!(matches!(
+            Circuit::new(&gates),
+            Err(CircuitError::Simulation(SimulationError::Cycle { .. }))
+        ));
+
+        let gates = [SimpleGate::new("x00", "abc", Op::And, "z00")];
+        assert_eq!(
+            Circuit::new(&gates).err(),
+            Some(CircuitError::UndrivenWire {
+                wire: "abc".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_bit() {
+        // z = x AND y without bit 1, the higher bits keep their numbers
+        let gates = [
+            SimpleGate::new("x00", "y00", Op::And, "z00"),
+            SimpleGate::new("x02", "y02", Op::And, "z02"),
+        ];
+        let circuit = Circuit::new(&gates).unwrap();
+
+        assert_eq!(circuit.input_bits(), (3, 3));
+        assert_eq!(circuit.output_bits(), 3);
+        assert_eq!(circuit.evaluate(0b111, 0b101), 0b101);
+
+        let masked = Reference::Custom(|x, y| x & y & 0b101);
+        assert_eq!(EquivalenceChecker::new(&circuit, masked).check(), None);
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt::Write;
+
+use hashbrown::{HashMap, HashSet};
+
+use super::op::{Op, SimpleGate};
+
+fn sorted_wires<'a>(wires: impl Iterator<Item = &'a String>) -> Vec<&'a str> {
+    let mut wires = wires
+        .map(|wire| wire.as_str())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    wires.sort();
+    wires
+}
+
+fn is_input(wire: &str) -> bool {
+    wire.starts_with('x') || wire.starts_with('y')
+}
+
+fn primitive(op: Op) -> &'static str {
+    match op {
+        Op::And => "and",
+        Op::Or => "or",
+        Op::Xor => "xor",
+    }
+}
+
+// One gate primitive per gate, `x` and `y` wires are the inputs and `z` wires the outputs
+pub fn to_verilog(gates: &[SimpleGate], module: &str) -> String {
+    let inputs = sorted_wires(
+        gates
+            .iter()
+            .flat_map(|gate| [&gate.a, &gate.b])
+            .filter(|wire| is_input(wire)),
+    );
+    let outputs = sorted_wires(
+        gates
+            .iter()
+            .map(|gate| &gate.c)
+            .filter(|wire| wire.starts_with('z')),
+    );
+    let internal = sorted_wires(
+        gates
+            .iter()
+            .map(|gate| &gate.c)
+            .filter(|wire| !wire.starts_with('z')),
+    );
+
+    let mut verilog = String::new();
+    writeln!(verilog, "module {} (", module).unwrap();
+
+    let ports = inputs
+        .iter()
+        .map(|wire| format!("    input wire {}", wire))
+        .chain(
+            outputs
+                .iter()
+                .map(|wire| format!("    output wire {}", wire)),
+        )
+        .collect::<Vec<_>>();
+    writeln!(verilog, "{}", ports.join(",\n")).unwrap();
+    writeln!(verilog, ");").unwrap();
+
+    if !internal.is_empty() {
+        writeln!(verilog).unwrap();
+        for wire in &internal {
+            writeln!(verilog, "    wire {};", wire).unwrap();
+        }
+    }
+
+    writeln!(verilog).unwrap();
+    for (i, gate) in gates.iter().enumerate() {
+        writeln!(
+            verilog,
+            "    {} g{} ({}, {}, {});",
+            primitive(gate.op),
+            i,
+            gate.c,
+            gate.a,
+            gate.b
+        )
+        .unwrap();
+    }
+
+    writeln!(verilog, "endmodule").unwrap();
+    verilog
+}
+
+// Gates are nodes labelled with their op and output wire, followed by its alias if it has
+// one. Inputs and outputs get nodes of their own so the graph reads left to right
+pub fn to_dot(gates: &[SimpleGate], aliases: &HashMap<String, String>) -> String {
+    let producers = gates
+        .iter()
+        .enumerate()
+        .map(|(i, gate)| (gate.c.as_str(), i))
+        .collect::<HashMap<_, _>>();
+
+    let inputs = sorted_wires(
+        gates
+            .iter()
+            .flat_map(|gate| [&gate.a, &gate.b])
+            .filter(|wire| !producers.contains_key(wire.as_str())),
+    );
+    let outputs = sorted_wires(
+        gates
+            .iter()
+            .map(|gate| &gate.c)
+            .filter(|wire| wire.starts_with('z')),
+    );
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph circuit {{").unwrap();
+    writeln!(dot, "    rankdir=LR;").unwrap();
+
+    for wire in inputs.iter().chain(&outputs) {
+        writeln!(dot, "    \"{}\" [shape=box];", wire).unwrap();
+    }
+
+    for (i, gate) in gates.iter().enumerate() {
+        let label = match aliases.get(&gate.c) {
+            Some(alias) => format!("{}\\n{} ({})", gate.op, gate.c, alias),
+            None => format!("{}\\n{}", gate.op, gate.c),
+        };
+        writeln!(dot, "    g{} [label=\"{}\"];", i, label).unwrap();
+    }
+
+    for (i, gate) in gates.iter().enumerate() {
+        for input in [&gate.a, &gate.b] {
+            match producers.get(input.as_str()) {
+                Some(producer) => writeln!(dot, "    g{} -> g{};", producer, i).unwrap(),
+                None => writeln!(dot, "    \"{}\" -> g{};", input, i).unwrap(),
+            }
+        }
+
+        if gate.c.starts_with('z') {
+            writeln!(dot, "    g{} -> \"{}\";", i, gate.c).unwrap();
+        }
+    }
+
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn half_adder() -> Vec<SimpleGate> {
+        vec![
+            SimpleGate::new("x00", "y00", Op::Xor, "z00"),
+            SimpleGate::new("y00", "x00", Op::And, "abc"),
+            SimpleGate::new("abc", "x00", Op::Or, "z01"),
+        ]
+    }
+
+    #[test]
+    fn test_to_verilog() {
+        let expected = "\
+module half_adder (
+    input wire x00,
+    input wire y00,
+    output wire z00,
+    output wire z01
+);
+
+    wire abc;
+
+    xor g0 (z00, x00, y00);
+    and g1 (abc, y00, x00);
+    or g2 (z01, abc, x00);
+endmodule
+";
+        assert_eq!(to_verilog(&half_adder(), "half_adder"), expected);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let aliases = HashMap::from([("abc".to_string(), "AND00".to_string())]);
+        let expected = "\
+digraph circuit {
+    rankdir=LR;
+    \"x00\" [shape=box];
+    \"y00\" [shape=box];
+    \"z00\" [shape=box];
+    \"z01\" [shape=box];
+    g0 [label=\"XOR\\nz00\"];
+    g1 [label=\"AND\\nabc (AND00)\"];
+    g2 [label=\"OR\\nz01\"];
+    \"x00\" -> g0;
+    \"y00\" -> g0;
+    g0 -> \"z00\";
+    \"y00\" -> g1;
+    \"x00\" -> g1;
+    g1 -> g2;
+    \"x00\" -> g2;
+    g2 -> \"z01\";
+}
+";
+        assert_eq!(to_dot(&half_adder(), &aliases), expected);
+    }
+}
@@ -1,12 +1,19 @@
-use std::{fmt::format, hash::Hash};
-
-use alias::Alias;
-use hashbrown::{HashMap, HashSet};
-use op::{Op, SimpleGate};
+use hashbrown::HashMap;
 
 mod alias;
+mod equivalence;
+mod export;
 mod heuristic;
 mod op;
+mod simulator;
+mod verifier;
+
+pub use alias::{AliasConflict, AliasRules, Renaming, RuleError, ADDER_ALIAS_RULES};
+pub use equivalence::{Circuit, CircuitError, Counterexample, EquivalenceChecker, Reference};
+pub use export::{to_dot, to_verilog};
+pub use op::{Op, SimpleGate};
+pub use simulator::{SimulationError, Simulator};
+pub use verifier::{AdderError, AdderVerifier, Fault, Problem, Role};
 
 fn parse_input(input: &str) -> (HashMap<String, bool>, Vec<SimpleGate>) {
     let mut initial_input = HashMap::new();
@@ -32,12 +39,7 @@ fn parse_input(input: &str) -> (HashMap<String, bool>, Vec<SimpleGate>) {
 
             let input_parts = input.split_whitespace().collect::<Vec<_>>();
 
-            let op = match input_parts[1] {
-                "AND" => Op::And,
-                "OR" => Op::Or,
-                "XOR" => Op::Xor,
-                _ => panic!("Invalid operator"),
-            };
+            let op = input_parts[1].parse().expect("Invalid operator");
 
             gates.push(SimpleGate::new(input_parts[0], input_parts[2], op, output));
         }
@@ -72,6 +74,80 @@ pub fn swap_wires(input: &str) -> Vec<String> {
     outputs
 }
 
+pub fn verify_adder(input: &str) -> Result<Vec<Fault>, AdderError> {
+    let (_, gates) = parse_input(input);
+    let faults = AdderVerifier::new(&gates)?.faults();
+    Ok(faults)
+}
+
+// Every swapped output shows up as a faulty gate, sorted the way the answer wants them
+pub fn find_faulty_wires(input: &str) -> Result<Vec<String>, AdderError> {
+    let mut wires = verify_adder(input)?
+        .into_iter()
+        .map(|fault| fault.gate.c)
+        .collect::<Vec<_>>();
+    wires.sort();
+    Ok(wires)
+}
+
+fn apply_swaps(gates: &mut [SimpleGate], swaps: &[(&str, &str)]) {
+    for gate in gates.iter_mut() {
+        for (a, b) in swaps {
+            if gate.c == *a {
+                gate.c = b.to_string();
+                break;
+            } else if gate.c == *b {
+                gate.c = a.to_string();
+                break;
+            }
+        }
+    }
+}
+
+// None when the circuit adds correctly on every vector tried once the outputs are swapped
+pub fn check_swaps(
+    input: &str,
+    swaps: &[(&str, &str)],
+) -> Result<Option<Counterexample>, CircuitError> {
+    let (_, mut gates) = parse_input(input);
+    apply_swaps(&mut gates, swaps);
+
+    let circuit = Circuit::new(&gates)?;
+    Ok(EquivalenceChecker::new(&circuit, Reference::Add).check())
+}
+
+// Tries every way of pairing up the wires until the swaps make a working adder
+pub fn pair_swapped_wires(input: &str, wires: &[String]) -> Option<Vec<(String, String)>> {
+    fn pair<'a>(input: &str, remaining: &[&'a str], pairs: &mut Vec<(&'a str, &'a str)>) -> bool {
+        let Some((first, rest)) = remaining.split_first() else {
+            return check_swaps(input, pairs).is_ok_and(|counterexample| counterexample.is_none());
+        };
+
+        for i in 0..rest.len() {
+            let mut others = rest.to_vec();
+            let second = others.remove(i);
+
+            pairs.push((first, second));
+            if pair(input, &others, pairs) {
+                return true;
+            }
+            pairs.pop();
+        }
+
+        false
+    }
+
+    let wires = wires.iter().map(|wire| wire.as_str()).collect::<Vec<_>>();
+    let mut pairs = Vec::new();
+
+    pair(input, &wires, &mut pairs).then(|| {
+        pairs
+            .into_iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    })
+}
+
 fn find_outputs_to_swap(gates: &mut [SimpleGate]) -> (Vec<String>, HashMap<String, String>) {
     let mut outputs = Vec::new();
     let mut additional_wire_renames = HashMap::new();
@@ -239,92 +315,39 @@ fn z_heuristic(gates: &[SimpleGate], n: usize) -> Option<(String, String)> {
     Some((expected_output, non_matching_output))
 }
 
+// Conflicting aliases are left out, the wires that would get them are where the adder is broken
 fn rename_gate_wires(gates: &mut [SimpleGate]) -> HashMap<String, String> {
-    let mut renamed_wires = HashMap::new();
-
-    Alias::new("x(N)", Op::And, "y(N)", "AND(N)").alias(gates, &mut renamed_wires);
-    Alias::new("x(N)", Op::Xor, "y(N)", "XOR(N)").alias(gates, &mut renamed_wires);
-
-    let and_00_original_wire = renamed_wires
-        .iter()
-        .find(|(_, v)| *v == "AND00")
-        .map(|(k, _)| k.clone())
-        .expect("Failed to find original wire for AND00");
-
-    // Rename all occurences of AND00 and the original wire to CARRY00
-
-    renamed_wires.insert(and_00_original_wire.clone(), "CARRY00".to_string());
-
-    for gate in gates.iter_mut() {
-        if gate.a == "AND00" {
-            gate.a = "CARRY00".to_string();
-        }
-        if gate.b == "AND00" {
-            gate.b = "CARRY00".to_string();
-        }
-
-        if gate.c == "AND00" {
-            gate.c = "CARRY00".to_string();
-        }
-    }
-
-    Alias::new("XOR(N)", Op::And, "CARRY(N-1)", "CARRY_INTERMEDIATE(N)")
-        .alias(gates, &mut renamed_wires);
-
-    Alias::new("AND(N)", Op::Or, "CARRY_INTERMEDIATE(N)", "CARRY(N)")
-        .alias(gates, &mut renamed_wires);
-
-    for g in gates {
-        println!("{}", g);
-    }
-
-    renamed_wires
+    AliasRules::adder().apply(gates).wires
 }
 
-pub fn compute_z_number(input: &str) -> usize {
-    let (initial_wires, gates) = parse_input(input);
-
-    let output_wires = simulate(initial_wires, gates);
-
-    let mut z = 0;
-
-    for (k, v) in output_wires.iter() {
-        if k.starts_with("z") && *v {
-            let num = k[1..].parse::<usize>().unwrap();
-            z |= 1 << num;
-        }
-    }
-
-    z
+// The original name of every wire that has an alias, mapped to the alias, and the wires that kept
+// their name because the alias was taken
+pub fn wire_aliases(gates: &[SimpleGate], rules: &AliasRules) -> Renaming {
+    rules.apply(&mut gates.to_vec())
 }
 
-fn simulate<'a>(mut input: HashMap<String, bool>, gates: Vec<SimpleGate>) -> HashMap<String, bool> {
-    let mut seen = HashSet::new();
-    loop {
-        let mut updated = false;
-
-        for gate in gates.iter() {
-            let g_a = gate.a.as_str();
-            let g_b = gate.b.as_str();
-            if input.contains_key(g_a) && input.contains_key(g_b) && !seen.contains(&gate) {
-                let a = input[g_a];
-                let b = input[g_b];
+pub fn alias_conflicts(input: &str, rules: &AliasRules) -> Vec<AliasConflict> {
+    let (_, gates) = parse_input(input);
+    wire_aliases(&gates, rules).conflicts
+}
 
-                let result = gate.evaluate(a, b);
+pub fn export_verilog(input: &str) -> String {
+    let (_, gates) = parse_input(input);
+    to_verilog(&gates, "circuit")
+}
 
-                updated = true;
+pub fn export_dot(input: &str, rules: &AliasRules) -> String {
+    let (_, gates) = parse_input(input);
+    to_dot(&gates, &wire_aliases(&gates, rules).wires)
+}
 
-                input.insert(gate.c.clone(), result);
-                seen.insert(gate);
-            }
-        }
+pub fn compute_z_number(input: &str) -> Result<usize, SimulationError> {
+    let (initial_wires, gates) = parse_input(input);
 
-        if !updated {
-            break;
-        }
-    }
+    let mut simulator = Simulator::new(&gates)?;
+    simulator.set_inputs(&initial_wires)?;
 
-    input
+    Ok(simulator.number('z'))
 }
 
 #[cfg(test)]
@@ -382,6 +405,33 @@ tnw OR pbm -> gnj";
 
     #[test]
     fn test_compute_z_number() {
-        assert_eq!(compute_z_number(TEST_INPUT), 2024);
+        assert_eq!(compute_z_number(TEST_INPUT), Ok(2024));
+
+        let looped = "x00: 1\n\nx00 AND z01 -> z00\nx00 AND z00 -> z01";
+        assert!(matches!(
+            compute_z_number(looped),
+            Err(SimulationError::Cycle { .. })
+        ));
+
+        let unknown = "w00: 1\n\nx00 AND x00 -> z00";
+        assert_eq!(
+            compute_z_number(unknown),
+            Err(SimulationError::UnknownWire {
+                wire: "w00".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let verilog = export_verilog(TEST_INPUT);
+        assert!(verilog.starts_with("module circuit (\n    input wire x00,\n"));
+        assert!(verilog.contains("    or g1 (tnw, y02, x01);\n"));
+
+        // Not an adder, x03 XOR y03 is the only gate that gets an alias
+        let dot = export_dot(TEST_INPUT, &AliasRules::adder());
+        assert!(dot.contains("    g26 [label=\"XOR\\nffh (XOR03)\"];\n"));
+        assert_eq!(dot.matches(" (").count(), 1);
+        assert!(dot.contains("    g1 -> g5;\n"));
     }
 }
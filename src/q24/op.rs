@@ -1,6 +1,7 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Op {
     And,
     Or,
@@ -17,6 +18,29 @@ impl Display for Op {
     }
 }
 
+impl Op {
+    pub fn evaluate(&self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a && b,
+            Op::Or => a || b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+impl FromStr for Op {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "XOR" => Ok(Op::Xor),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SimpleGate {
     pub a: String,
@@ -38,11 +62,7 @@ impl SimpleGate {
 
 impl SimpleGate {
     pub fn evaluate(&self, a: bool, b: bool) -> bool {
-        match self.op {
-            Op::And => a && b,
-            Op::Or => a || b,
-            Op::Xor => a ^ b,
-        }
+        self.op.evaluate(a, b)
     }
 }
 
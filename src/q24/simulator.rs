@@ -0,0 +1,425 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Display;
+
+use hashbrown::HashMap;
+
+use super::op::{Op, SimpleGate};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationError {
+    // The wires of one loop, in the order the signal travels, starting from the smallest name
+    Cycle { wires: Vec<String> },
+    MultipleDrivers { wire: String },
+    UnknownWire { wire: String },
+    // Only wires no gate drives can be set from outside
+    DrivenWire { wire: String },
+}
+
+impl Display for SimulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cycle { wires } => write!(f, "combinational cycle {}", wires.join(" -> ")),
+            Self::MultipleDrivers { wire } => write!(f, "wire {} is driven by several gates", wire),
+            Self::UnknownWire { wire } => write!(f, "wire {} is not part of the circuit", wire),
+            Self::DrivenWire { wire } => write!(f, "wire {} is driven by a gate", wire),
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
+// Gates are ordered once so that every gate comes after the gates driving its inputs. Wires
+// without a value yet are unknown, as are the outputs of gates reading them
+pub struct Simulator {
+    indices: HashMap<String, usize>,
+    names: Vec<String>,
+    gates: Vec<(usize, usize, Op, usize)>,
+    drivers: Vec<Option<usize>>,
+    // Per wire, the positions in `gates` of the gates reading it
+    readers: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    evaluations: usize,
+}
+
+impl Simulator {
+    pub fn new(gates: &[SimpleGate]) -> Result<Self, SimulationError> {
+        let mut indices = HashMap::new();
+        let mut names = Vec::new();
+        let wires = gates
+            .iter()
+            .map(|gate| {
+                [&gate.a, &gate.b, &gate.c].map(|wire| {
+                    *indices.entry(wire.clone()).or_insert_with(|| {
+                        names.push(wire.clone());
+                        names.len() - 1
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut drivers = vec![None; names.len()];
+        for (i, [_, _, c]) in wires.iter().enumerate() {
+            if drivers[*c].replace(i).is_some() {
+                return Err(SimulationError::MultipleDrivers {
+                    wire: names[*c].clone(),
+                });
+            }
+        }
+
+        // Kahn's algorithm over the original gate indices, counting inputs driven by gates
+        let mut readers = vec![Vec::new(); names.len()];
+        let mut pending = vec![0; gates.len()];
+        for (i, [a, b, _]) in wires.iter().enumerate() {
+            for input in [a, b] {
+                if drivers[*input].is_some() {
+                    readers[*input].push(i);
+                    pending[i] += 1;
+                }
+            }
+        }
+
+        let mut ready = (0..gates.len())
+            .filter(|i| pending[*i] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(gates.len());
+        while let Some(i) = ready.pop_front() {
+            order.push(i);
+            for reader in &readers[wires[i][2]] {
+                pending[*reader] -= 1;
+                if pending[*reader] == 0 {
+                    ready.push_back(*reader);
+                }
+            }
+        }
+
+        if order.len() < gates.len() {
+            let mut wires = find_cycle(&wires, &drivers, &pending)
+                .into_iter()
+                .map(|wire| names[wire].clone())
+                .collect::<Vec<_>>();
+            let smallest = (0..wires.len()).min_by_key(|i| &wires[*i]).unwrap();
+            wires.rotate_left(smallest);
+            return Err(SimulationError::Cycle { wires });
+        }
+
+        let mut positions = vec![0; gates.len()];
+        for (position, i) in order.iter().enumerate() {
+            positions[*i] = position;
+        }
+
+        // Every input this time, undriven ones included, as positions in the new order
+        let mut readers = vec![Vec::new(); names.len()];
+        for (i, [a, b, _]) in wires.iter().enumerate() {
+            readers[*a].push(positions[i]);
+            readers[*b].push(positions[i]);
+        }
+        for positions in readers.iter_mut() {
+            positions.sort();
+            positions.dedup();
+        }
+
+        let drivers = drivers
+            .into_iter()
+            .map(|driver| driver.map(|i| positions[i]))
+            .collect();
+
+        let gates = order
+            .into_iter()
+            .map(|i| {
+                let [a, b, c] = wires[i];
+                (a, b, gates[i].op, c)
+            })
+            .collect();
+
+        Ok(Self {
+            values: vec![None; names.len()],
+            indices,
+            names,
+            gates,
+            drivers,
+            readers,
+            evaluations: 0,
+        })
+    }
+
+    // Wire names by index, in the order they first appear in the gates
+    pub(super) fn wire_names(&self) -> &[String] {
+        &self.names
+    }
+
+    // (a, b, op, c) over wire indices, every gate after the gates driving its inputs
+    pub(super) fn ordered_gates(&self) -> &[(usize, usize, Op, usize)] {
+        &self.gates
+    }
+
+    pub(super) fn is_driven(&self, index: usize) -> bool {
+        self.drivers[index].is_some()
+    }
+
+    fn index(&self, wire: &str) -> Result<usize, SimulationError> {
+        let index = *self
+            .indices
+            .get(wire)
+            .ok_or_else(|| SimulationError::UnknownWire {
+                wire: wire.to_string(),
+            })?;
+
+        match self.drivers[index] {
+            Some(_) => Err(SimulationError::DrivenWire {
+                wire: wire.to_string(),
+            }),
+            None => Ok(index),
+        }
+    }
+
+    fn evaluate(&self, position: usize) -> Option<bool> {
+        let (a, b, op, _) = self.gates[position];
+        Some(op.evaluate(self.values[a]?, self.values[b]?))
+    }
+
+    // Forgets every value and evaluates the whole circuit from these inputs
+    pub fn set_inputs(&mut self, inputs: &HashMap<String, bool>) -> Result<(), SimulationError> {
+        let mut values = vec![None; self.names.len()];
+        for (wire, value) in inputs {
+            values[self.index(wire)?] = Some(*value);
+        }
+        self.values = values;
+
+        for position in 0..self.gates.len() {
+            self.values[self.gates[position].3] = self.evaluate(position);
+        }
+        self.evaluations = self.gates.len();
+
+        Ok(())
+    }
+
+    // Changes one input and re-evaluates only the gates downstream of a changed wire. Returns
+    // the wires whose value changed, in the order they were updated
+    pub fn set_input(&mut self, wire: &str, value: bool) -> Result<Vec<&str>, SimulationError> {
+        let index = self.index(wire)?;
+        self.evaluations = 0;
+        if self.values[index] == Some(value) {
+            return Ok(Vec::new());
+        }
+
+        self.values[index] = Some(value);
+        let mut changed = vec![index];
+        let mut queue = self.readers[index].iter().copied().collect::<BTreeSet<_>>();
+
+        // Gates are taken in topological order, so each one is evaluated at most once
+        while let Some(position) = queue.pop_first() {
+            self.evaluations += 1;
+            let output = self.gates[position].3;
+            let value = self.evaluate(position);
+            if self.values[output] != value {
+                self.values[output] = value;
+                changed.push(output);
+                queue.extend(&self.readers[output]);
+            }
+        }
+
+        Ok(changed
+            .into_iter()
+            .map(|wire| self.names[wire].as_str())
+            .collect())
+    }
+
+    // The number of gates evaluated by the last call to `set_inputs` or `set_input`
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    pub fn value(&self, wire: &str) -> Option<bool> {
+        self.indices.get(wire).and_then(|index| self.values[*index])
+    }
+
+    pub fn values(&self) -> HashMap<String, bool> {
+        self.names
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(wire, value)| Some((wire.clone(), (*value)?)))
+            .collect()
+    }
+
+    // The wires driven by gates reading this wire
+    pub fn fan_out(&self, wire: &str) -> Vec<&str> {
+        self.indices.get(wire).map_or(Vec::new(), |index| {
+            self.readers[*index]
+                .iter()
+                .map(|position| self.names[self.gates[*position].3].as_str())
+                .collect()
+        })
+    }
+
+    // The number whose bits are the known set wires starting with `prefix`. Wires numbered past
+    // the width of usize are skipped
+    pub fn number(&self, prefix: char) -> usize {
+        self.names
+            .iter()
+            .zip(&self.values)
+            .filter(|(_, value)| **value == Some(true))
+            .filter_map(|(wire, _)| wire.strip_prefix(prefix)?.parse::<u32>().ok())
+            .filter_map(|bit| 1usize.checked_shl(bit))
+            .fold(0, |number, bit| number | bit)
+    }
+}
+
+// Walks backwards from a gate left over by Kahn's algorithm through inputs driven by other
+// left over gates, which must come back to a gate already visited
+fn find_cycle(wires: &[[usize; 3]], drivers: &[Option<usize>], pending: &[usize]) -> Vec<usize> {
+    let mut gate = (0..wires.len()).find(|i| pending[*i] > 0).unwrap();
+    let mut visited = Vec::new();
+
+    let start = loop {
+        if let Some(start) = visited.iter().position(|visited| *visited == gate) {
+            break start;
+        }
+        visited.push(gate);
+
+        let [a, b, _] = wires[gate];
+        gate = [a, b]
+            .into_iter()
+            .filter_map(|input| drivers[input])
+            .find(|driver| pending[*driver] > 0)
+            .unwrap();
+    };
+
+    // Walking backwards, so reversed to follow the signal
+    visited[start..]
+        .iter()
+        .rev()
+        .map(|gate| wires[*gate][2])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_adder() -> Vec<SimpleGate> {
+        vec![
+            SimpleGate::new("x00", "y00", Op::Xor, "hs0"),
+            SimpleGate::new("hs0", "c00", Op::Xor, "z00"),
+            SimpleGate::new("x00", "y00", Op::And, "hc0"),
+            SimpleGate::new("hs0", "c00", Op::And, "ci0"),
+            SimpleGate::new("hc0", "ci0", Op::Or, "z01"),
+        ]
+    }
+
+    fn inputs(x: bool, y: bool, c: bool) -> HashMap<String, bool> {
+        HashMap::from([
+            ("x00".to_string(), x),
+            ("y00".to_string(), y),
+            ("c00".to_string(), c),
+        ])
+    }
+
+    #[test]
+    fn test_set_inputs() {
+        let mut simulator = Simulator::new(&full_adder()).unwrap();
+        for n in 0..8 {
+            let (x, y, c) = (n & 1 == 1, n & 2 == 2, n & 4 == 4);
+            simulator.set_inputs(&inputs(x, y, c)).unwrap();
+            assert_eq!(
+                simulator.number('z'),
+                (x as usize) + (y as usize) + (c as usize)
+            );
+            assert_eq!(simulator.evaluations(), 5);
+        }
+
+        assert_eq!(simulator.values().len(), 8);
+    }
+
+    #[test]
+    fn test_unknown_values() {
+        let mut simulator = Simulator::new(&full_adder()).unwrap();
+        let partial = HashMap::from([("x00".to_string(), true), ("y00".to_string(), true)]);
+        simulator.set_inputs(&partial).unwrap();
+
+        assert_eq!(simulator.value("hs0"), Some(false));
+        assert_eq!(simulator.value("hc0"), Some(true));
+        assert_eq!(simulator.value("z00"), None);
+        assert_eq!(simulator.value("z01"), None);
+        assert_eq!(simulator.value("nope"), None);
+    }
+
+    #[test]
+    fn test_set_input_is_incremental() {
+        let mut simulator = Simulator::new(&full_adder()).unwrap();
+        simulator.set_inputs(&inputs(true, false, false)).unwrap();
+        assert_eq!(simulator.number('z'), 1);
+
+        // Only the gates reading the carry in are looked at
+        assert_eq!(
+            simulator.set_input("c00", true),
+            Ok(vec!["c00", "z00", "ci0", "z01"])
+        );
+        assert_eq!(simulator.evaluations(), 3);
+        assert_eq!(simulator.number('z'), 2);
+
+        assert_eq!(simulator.set_input("c00", true), Ok(vec![]));
+        assert_eq!(simulator.evaluations(), 0);
+
+        let changed = simulator.set_input("y00", true).unwrap();
+        assert_eq!(changed, vec!["y00", "hs0", "hc0", "z00", "ci0"]);
+        assert_eq!(simulator.number('z'), 3);
+
+        assert_eq!(
+            simulator.set_input("hs0", true),
+            Err(SimulationError::DrivenWire {
+                wire: "hs0".to_string()
+            })
+        );
+        assert_eq!(
+            simulator.set_input("x01", true),
+            Err(SimulationError::UnknownWire {
+                wire: "x01".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_fan_out() {
+        let simulator = Simulator::new(&full_adder()).unwrap();
+        assert_eq!(simulator.fan_out("x00"), vec!["hs0", "hc0"]);
+        assert_eq!(simulator.fan_out("hs0"), vec!["z00", "ci0"]);
+        assert_eq!(simulator.fan_out("z01"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_number_skips_wide_bits() {
+        let gates = [
+            SimpleGate::new("x00", "x00", Op::Or, "z63"),
+            SimpleGate::new("x00", "x00", Op::Or, "z64"),
+            SimpleGate::new("x00", "x00", Op::Or, "z99999999999"),
+        ];
+        let mut simulator = Simulator::new(&gates).unwrap();
+        simulator
+            .set_inputs(&HashMap::from([("x00".to_string(), true)]))
+            .unwrap();
+
+        assert_eq!(simulator.number('z'), 1 << 63);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut gates = full_adder();
+        // Feeding the carry out back into the half sum
+        gates[0] = SimpleGate::new("x00", "z01", Op::Xor, "hs0");
+
+        assert_eq!(
+            Simulator::new(&gates).err(),
+            Some(SimulationError::Cycle {
+                wires: vec!["ci0".to_string(), "z01".to_string(), "hs0".to_string()]
+            })
+        );
+
+        gates[0] = SimpleGate::new("x00", "y00", Op::Xor, "hc0");
+        assert_eq!(
+            Simulator::new(&gates).err(),
+            Some(SimulationError::MultipleDrivers {
+                wire: "hc0".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use std::fmt::Display;
+
+use hashbrown::HashMap;
+
+use super::op::{Op, SimpleGate};
+
+// A ripple carry adder of n bits is built from, for every bit i
+//   half sum          s(i) = x(i) XOR y(i)
+//   half carry        h(i) = x(i) AND y(i)
+//   sum               z(i) = s(i) XOR c(i-1)
+//   carry intermediate t(i) = s(i) AND c(i-1)
+//   carry             c(i) = h(i) OR t(i)
+// except bit 0, where z00 is the half sum and c(0) the half carry, and z(n) = c(n-1).
+// Every gate is given the role its op and inputs imply, then checked against where its
+// output goes, so faults are found however many outputs were swapped
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdderError {
+    NoInputs,
+    WidthMismatch { x: usize, y: usize, z: usize },
+}
+
+impl Display for AdderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoInputs => write!(f, "no x or y wires feed the circuit"),
+            Self::WidthMismatch { x, y, z } => write!(
+                f,
+                "{} x bits and {} y bits cannot be added into {} z bits",
+                x, y, z
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AdderError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    HalfSum(usize),
+    HalfCarry(usize),
+    // The bit is unknown when no input identifies it
+    Sum(Option<usize>),
+    CarryIntermediate(Option<usize>),
+    Carry(Option<usize>),
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, bit) = match self {
+            Self::HalfSum(bit) => ("half sum", Some(*bit)),
+            Self::HalfCarry(bit) => ("half carry", Some(*bit)),
+            Self::Sum(bit) => ("sum", *bit),
+            Self::CarryIntermediate(bit) => ("carry intermediate", *bit),
+            Self::Carry(bit) => ("carry", *bit),
+        };
+
+        match bit {
+            Some(bit) => write!(f, "{} of bit {}", name, bit),
+            None => write!(f, "{} of an unknown bit", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    // Only sums and the final carry may drive z wires
+    DrivesOutput { wire: String },
+    WrongOutput { expected: String },
+    WrongConsumers { expected: Vec<Op>, actual: Vec<Op> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub gate: SimpleGate,
+    pub role: Role,
+    pub problem: Problem,
+}
+
+fn format_ops(ops: &[Op]) -> String {
+    match ops.is_empty() {
+        true => "nothing".to_string(),
+        false => ops
+            .iter()
+            .map(|op| op.to_string())
+            .collect::<Vec<_>>()
+            .join(" and "),
+    }
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ", self.gate, self.role)?;
+
+        match &self.problem {
+            Problem::DrivesOutput { wire } => write!(f, "drives the output {}", wire),
+            Problem::WrongOutput { expected } => {
+                write!(f, "should drive {} but drives {}", expected, self.gate.c)
+            }
+            Problem::WrongConsumers { expected, actual } => write!(
+                f,
+                "should feed {} but feeds {}",
+                format_ops(expected),
+                format_ops(actual)
+            ),
+        }
+    }
+}
+
+fn wire_bit(wire: &str, prefix: char) -> Option<usize> {
+    wire.strip_prefix(prefix)?.parse().ok()
+}
+
+fn wire_name(prefix: char, bit: usize) -> String {
+    format!("{}{:0>2}", prefix, bit)
+}
+
+// Bits are numbered from zero, so the width is one more than the highest bit seen
+fn width(wires: impl Iterator<Item = Option<usize>>) -> usize {
+    wires.flatten().max().map_or(0, |bit| bit + 1)
+}
+
+pub struct AdderVerifier<'a> {
+    gates: &'a [SimpleGate],
+    width: usize,
+    roles: Vec<Role>,
+    // The ops of every gate reading a wire, sorted
+    consumers: HashMap<&'a str, Vec<Op>>,
+}
+
+impl<'a> AdderVerifier<'a> {
+    pub fn new(gates: &'a [SimpleGate]) -> Result<Self, AdderError> {
+        let inputs = || {
+            gates
+                .iter()
+                .flat_map(|gate| [gate.a.as_str(), gate.b.as_str()])
+        };
+        let x = width(inputs().map(|wire| wire_bit(wire, 'x')));
+        let y = width(inputs().map(|wire| wire_bit(wire, 'y')));
+        let z = width(gates.iter().map(|gate| wire_bit(&gate.c, 'z')));
+
+        if x == 0 && y == 0 {
+            return Err(AdderError::NoInputs);
+        }
+        if x != y || z != x + 1 {
+            return Err(AdderError::WidthMismatch { x, y, z });
+        }
+
+        let mut consumers: HashMap<&str, Vec<Op>> = HashMap::new();
+        for gate in gates {
+            for input in [&gate.a, &gate.b] {
+                consumers.entry(input.as_str()).or_default().push(gate.op);
+            }
+        }
+        for ops in consumers.values_mut() {
+            ops.sort();
+        }
+
+        let roles = Self::roles(gates);
+
+        Ok(Self {
+            gates,
+            width: x,
+            roles,
+            consumers,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn role(&self, gate: usize) -> Role {
+        self.roles[gate]
+    }
+
+    // Gates reading x and y are known from their inputs, the rest take the bit of the role
+    // feeding them, each layer depending on the one before
+    fn roles(gates: &[SimpleGate]) -> Vec<Role> {
+        let input_bit = |gate: &SimpleGate| {
+            let x = wire_bit(&gate.a, 'x').or(wire_bit(&gate.b, 'x'))?;
+            let y = wire_bit(&gate.a, 'y').or(wire_bit(&gate.b, 'y'))?;
+            (x == y).then_some(x)
+        };
+
+        let mut roles = gates
+            .iter()
+            .map(|gate| match (gate.op, input_bit(gate)) {
+                (Op::Xor, Some(0)) => Role::Sum(Some(0)),
+                (Op::And, Some(0)) => Role::Carry(Some(0)),
+                (Op::Xor, Some(bit)) => Role::HalfSum(bit),
+                (Op::And, Some(bit)) => Role::HalfCarry(bit),
+                (Op::Xor, None) => Role::Sum(None),
+                (Op::And, None) => Role::CarryIntermediate(None),
+                (Op::Or, _) => Role::Carry(None),
+            })
+            .collect::<Vec<_>>();
+
+        let producers = gates
+            .iter()
+            .enumerate()
+            .map(|(i, gate)| (gate.c.as_str(), i))
+            .collect::<HashMap<_, _>>();
+
+        let input_roles = |roles: &[Role], gate: &SimpleGate| {
+            [&gate.a, &gate.b]
+                .into_iter()
+                .filter_map(|wire| producers.get(wire.as_str()).map(|i| roles[*i]))
+                .collect::<Vec<_>>()
+        };
+
+        // Sums and carry intermediates read the half sum of their bit or the previous carry,
+        // carries the half carry or carry intermediate of their bit. Each bit found can
+        // identify the next gate along, so this repeats until nothing changes
+        let bit = |role: Role, inputs: &[Role]| {
+            inputs.iter().find_map(|input| match (role, input) {
+                (Role::Sum(_) | Role::CarryIntermediate(_), Role::HalfSum(bit)) => Some(*bit),
+                (Role::Sum(_) | Role::CarryIntermediate(_), Role::Carry(Some(bit))) => {
+                    Some(bit + 1)
+                }
+                (Role::Carry(_), Role::HalfCarry(bit)) => Some(*bit),
+                (Role::Carry(_), Role::CarryIntermediate(bit)) => *bit,
+                _ => None,
+            })
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (i, gate) in gates.iter().enumerate() {
+                let role = match roles[i] {
+                    Role::Sum(None) => Role::Sum(bit(roles[i], &input_roles(&roles, gate))),
+                    Role::CarryIntermediate(None) => {
+                        Role::CarryIntermediate(bit(roles[i], &input_roles(&roles, gate)))
+                    }
+                    Role::Carry(None) => Role::Carry(bit(roles[i], &input_roles(&roles, gate))),
+                    role => role,
+                };
+
+                changed |= role != roles[i];
+                roles[i] = role;
+            }
+        }
+
+        roles
+    }
+
+    fn check(&self, gate: &SimpleGate, role: Role) -> Option<Problem> {
+        let last = self.width - 1;
+        let output = wire_bit(&gate.c, 'z');
+        let actual = self
+            .consumers
+            .get(gate.c.as_str())
+            .cloned()
+            .unwrap_or_default();
+
+        let expect_consumers = |expected: Vec<Op>| {
+            (actual != expected).then(|| Problem::WrongConsumers {
+                expected,
+                actual: actual.clone(),
+            })
+        };
+        let drives_output = || {
+            output.map(|_| Problem::DrivesOutput {
+                wire: gate.c.clone(),
+            })
+        };
+
+        match role {
+            Role::HalfSum(_) => {
+                drives_output().or_else(|| expect_consumers(vec![Op::And, Op::Xor]))
+            }
+            Role::HalfCarry(_) | Role::CarryIntermediate(_) => {
+                drives_output().or_else(|| expect_consumers(vec![Op::Or]))
+            }
+            Role::Sum(bit) => match (bit, output) {
+                (Some(bit), Some(z)) if bit == z => None,
+                (Some(bit), _) => Some(Problem::WrongOutput {
+                    expected: wire_name('z', bit),
+                }),
+                (None, Some(z)) if z <= last => None,
+                (None, Some(_)) => drives_output(),
+                (None, None) => Some(Problem::WrongOutput {
+                    expected: "a z wire".to_string(),
+                }),
+            },
+            Role::Carry(bit) => {
+                let final_carry = bit.is_none_or(|bit| bit == last);
+                match output {
+                    Some(z) if z == last + 1 && final_carry => None,
+                    Some(_) => drives_output(),
+                    None if bit == Some(last) => Some(Problem::WrongOutput {
+                        expected: wire_name('z', last + 1),
+                    }),
+                    None => expect_consumers(vec![Op::And, Op::Xor]),
+                }
+            }
+        }
+    }
+
+    pub fn faults(&self) -> Vec<Fault> {
+        let mut faults = self
+            .gates
+            .iter()
+            .zip(&self.roles)
+            .filter_map(|(gate, role)| {
+                self.check(gate, *role).map(|problem| Fault {
+                    gate: gate.clone(),
+                    role: *role,
+                    problem,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        faults.sort_by(|a, b| a.gate.c.cmp(&b.gate.c));
+        faults
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A correct adder of `width` bits, named the way the puzzle input is
+    fn adder(width: usize) -> Vec<SimpleGate> {
+        let carry = if width == 1 { "z01" } else { "c00" };
+        let mut gates = vec![
+            SimpleGate::new("x00", "y00", Op::Xor, "z00"),
+            SimpleGate::new("x00", "y00", Op::And, carry),
+        ];
+
+        for i in 1..width {
+            let (x, y, z) = (wire_name('x', i), wire_name('y', i), wire_name('z', i));
+            let carry_in = wire_name('c', i - 1);
+            let carry = match i == width - 1 {
+                true => wire_name('z', width),
+                false => wire_name('c', i),
+            };
+
+            gates.extend([
+                SimpleGate::new(&x, &y, Op::Xor, &wire_name('s', i)),
+                SimpleGate::new(&y, &x, Op::And, &wire_name('h', i)),
+                SimpleGate::new(&carry_in, &wire_name('s', i), Op::Xor, &z),
+                SimpleGate::new(&wire_name('s', i), &carry_in, Op::And, &wire_name('t', i)),
+                SimpleGate::new(&wire_name('h', i), &wire_name('t', i), Op::Or, &carry),
+            ]);
+        }
+
+        gates
+    }
+
+    fn swap(gates: &mut [SimpleGate], a: &str, b: &str) {
+        for gate in gates {
+            if gate.c == a {
+                gate.c = b.to_string();
+            } else if gate.c == b {
+                gate.c = a.to_string();
+            }
+        }
+    }
+
+    fn faulty_wires(gates: &[SimpleGate]) -> Vec<String> {
+        let verifier = AdderVerifier::new(gates).unwrap();
+        verifier
+            .faults()
+            .into_iter()
+            .map(|fault| fault.gate.c)
+            .collect()
+    }
+
+    #[test]
+    fn test_correct_adder_has_no_faults() {
+        for width in [1, 2, 5, 45] {
+            let gates = adder(width);
+            let verifier = AdderVerifier::new(&gates).unwrap();
+
+            assert_eq!(verifier.width(), width);
+            assert_eq!(verifier.faults(), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_finds_every_swap() {
+        let mut gates = adder(12);
+        // Half sum with half carry, a sum with a carry, a sum with a carry intermediate and
+        // two sums of different bits
+        let swaps = [
+            ("s03", "h03"),
+            ("z05", "c05"),
+            ("z07", "t08"),
+            ("z10", "z11"),
+        ];
+        for (a, b) in swaps {
+            swap(&mut gates, a, b);
+        }
+
+        let mut expected = swaps
+            .iter()
+            .flat_map(|(a, b)| [a.to_string(), b.to_string()])
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(faulty_wires(&gates), expected);
+    }
+
+    #[test]
+    fn test_fault_explanations() {
+        let mut gates = adder(4);
+        swap(&mut gates, "z02", "c02");
+
+        let faults = AdderVerifier::new(&gates).unwrap().faults();
+        let explanations = faults.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            explanations,
+            vec![
+                "c01 XOR s02 -> c02: sum of bit 2 should drive z02 but drives c02",
+                "h02 OR t02 -> z02: carry of bit 2 drives the output z02",
+            ]
+        );
+        assert_eq!(faults[1].role, Role::Carry(Some(2)));
+    }
+
+    #[test]
+    fn test_width_errors() {
+        let mut gates = adder(4);
+        gates.retain(|gate| gate.c != "z04");
+
+        assert!(matches!(
+            AdderVerifier::new(&gates),
+            Err(AdderError::WidthMismatch { x: 4, y: 4, z: 4 })
+        ));
+        assert!(matches!(AdderVerifier::new(&[]), Err(AdderError::NoInputs)));
+    }
+}
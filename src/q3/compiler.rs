@@ -1,22 +1,117 @@
+use std::fmt::Display;
+
 use crate::tokenizer::Tokenizer;
 
+use super::diagnostics::{Diagnostic, DiagnosticKind};
 use super::tokenizer::{Token, TokenType};
 
+// Numbers take one to three digits, and the parts of a call cannot be spaced out
+pub(super) const MAX_DIGITS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Mul,
+    Add,
+    Sub,
+    Div,
+}
+
+impl BinaryOp {
+    fn from_token(token_type: TokenType) -> Option<Self> {
+        match token_type {
+            TokenType::Mul => Some(Self::Mul),
+            TokenType::Add => Some(Self::Add),
+            TokenType::Sub => Some(Self::Sub),
+            TokenType::Div => Some(Self::Div),
+            _ => None,
+        }
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mul => write!(f, "mul"),
+            Self::Add => write!(f, "add"),
+            Self::Sub => write!(f, "sub"),
+            Self::Div => write!(f, "div"),
+        }
+    }
+}
+
+// An argument of a call in the extended language
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Literal(i64),
+    Register(String),
+    Call(BinaryOp, Box<Value>, Box<Value>),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Literal(n) => write!(f, "{}", n),
+            Self::Register(name) => write!(f, "${}", name),
+            Self::Call(op, a, b) => write!(f, "{}({},{})", op, a, b),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Expr {
     Mul(i32, i32),
     Do,
     Dont,
+    // Only produced by the extended language
+    Call(BinaryOp, Value, Value),
+    Set(String, Value),
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mul(a, b) => write!(f, "mul({},{})", a, b),
+            Self::Do => write!(f, "do()"),
+            Self::Dont => write!(f, "don't()"),
+            Self::Call(op, a, b) => write!(f, "{}({},{})", op, a, b),
+            Self::Set(name, value) => write!(f, "set(${},{})", name, value),
+        }
+    }
 }
 
 pub struct Compiler<'a> {
     tokens: &'a [Token],
     source: &'a str,
+    extended: bool,
+    diagnostics: Option<Vec<Diagnostic>>,
 }
 
 impl<'a> Compiler<'a> {
     pub fn new(tokens: &'a [Token], source: &'a str) -> Self {
-        Self { tokens, source }
+        Self {
+            tokens,
+            source,
+            extended: false,
+            diagnostics: None,
+        }
+    }
+
+    // Besides `mul` of two numbers, accept `add`, `sub` and `div`, calls and registers as
+    // arguments, and `set($name,value)`
+    pub fn extended(mut self) -> Self {
+        self.extended = true;
+        self
+    }
+
+    // Records why every call that was started with a keyword and `(` got rejected
+    pub fn with_diagnostics(mut self) -> Self {
+        self.diagnostics = Some(Vec::new());
+        self
+    }
+
+    // Everything rejected so far, empty unless built `with_diagnostics`
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        self.diagnostics.as_deref().unwrap_or_default()
     }
 }
 
@@ -48,7 +143,7 @@ impl Compiler<'_> {
             }
         }
 
-        if !valid {
+        if !valid || !self.contiguous(3) {
             return None;
         }
 
@@ -73,7 +168,7 @@ impl Compiler<'_> {
             }
         }
 
-        if !valid {
+        if !valid || !self.contiguous(3) {
             return None;
         }
 
@@ -83,6 +178,10 @@ impl Compiler<'_> {
     }
 
     fn parse_mul(&mut self) -> Option<(i32, i32)> {
+        if self.eof_at(5) {
+            return None;
+        }
+
         let valid_sequence = [
             TokenType::Mul,
             TokenType::LeftParen,
@@ -101,17 +200,20 @@ impl Compiler<'_> {
             }
         }
 
-        if !valid {
+        if !valid || !self.contiguous(6) {
             return None;
         }
 
         let a_token = self.peek_at(2);
+        let b_token = self.peek_at(4);
+        if a_token.length > MAX_DIGITS || b_token.length > MAX_DIGITS {
+            return None;
+        }
 
         let a = self.source[a_token.pos..a_token.pos + a_token.length]
             .parse::<i32>()
             .unwrap();
 
-        let b_token = self.peek_at(4);
         let b = self.source[b_token.pos..b_token.pos + b_token.length]
             .parse::<i32>()
             .unwrap();
@@ -121,6 +223,165 @@ impl Compiler<'_> {
         Some((a, b))
     }
 
+    fn token_text(&self, token: Token) -> &str {
+        &self.source[token.pos..token.pos + token.length]
+    }
+
+    // Whether the token `offset` ahead directly follows the one before it
+    fn adjacent(&self, offset: usize) -> bool {
+        offset == 0 || {
+            let previous = self.peek_at(offset - 1);
+            previous.pos + previous.length == self.peek_at(offset).pos
+        }
+    }
+
+    fn contiguous(&self, count: usize) -> bool {
+        (1..count).all(|offset| self.adjacent(offset))
+    }
+
+    fn expect_at(&self, offset: usize, token_type: TokenType) -> Option<()> {
+        (self.maybe_peek_at(offset)?.token_type == token_type && self.adjacent(offset))
+            .then_some(())
+    }
+
+    // A value starting `offset` tokens ahead, with the number of tokens it spans. Nothing is
+    // consumed, so a failed attempt leaves the tokens as they were
+    fn parse_value(&self, offset: usize) -> Option<(Value, usize)> {
+        let token = self.maybe_peek_at(offset)?;
+        if !self.adjacent(offset) {
+            return None;
+        }
+
+        match token.token_type {
+            TokenType::NumericLiteral if token.length <= MAX_DIGITS => {
+                let n = self.token_text(token).parse().ok()?;
+                Some((Value::Literal(n), 1))
+            }
+            TokenType::Register => {
+                let name = self.token_text(token)[1..].to_string();
+                Some((Value::Register(name), 1))
+            }
+            _ => {
+                let (op, a, b, length) = self.parse_call(offset)?;
+                Some((Value::Call(op, Box::new(a), Box::new(b)), length))
+            }
+        }
+    }
+
+    fn parse_call(&self, offset: usize) -> Option<(BinaryOp, Value, Value, usize)> {
+        let op = BinaryOp::from_token(self.maybe_peek_at(offset)?.token_type)?;
+        let mut n = offset + 1;
+
+        self.expect_at(n, TokenType::LeftParen)?;
+        let (a, length) = self.parse_value(n + 1)?;
+        n += 1 + length;
+
+        self.expect_at(n, TokenType::Comma)?;
+        let (b, length) = self.parse_value(n + 1)?;
+        n += 1 + length;
+
+        self.expect_at(n, TokenType::RightParen)?;
+
+        Some((op, a, b, n + 1 - offset))
+    }
+
+    fn parse_set(&mut self) -> Option<Expr> {
+        self.expect_at(0, TokenType::Set)?;
+        self.expect_at(1, TokenType::LeftParen)?;
+        self.expect_at(2, TokenType::Register)?;
+        self.expect_at(3, TokenType::Comma)?;
+        let (value, length) = self.parse_value(4)?;
+        self.expect_at(4 + length, TokenType::RightParen)?;
+
+        let name = self.token_text(self.peek_at(2))[1..].to_string();
+        self.advance(5 + length);
+
+        Some(Expr::Set(name, value))
+    }
+
+    fn parse_extended(&mut self) -> Option<Expr> {
+        if let Some(set) = self.parse_set() {
+            return Some(set);
+        }
+
+        let (op, a, b, length) = self.parse_call(0)?;
+        self.advance(length);
+
+        Some(Expr::Call(op, a, b))
+    }
+
+    fn report(&mut self, kind: DiagnosticKind, start: Token, end: Token) {
+        let span = start.pos..end.pos + end.length;
+        if let Some(diagnostics) = self.diagnostics.as_mut() {
+            diagnostics.push(Diagnostic { kind, span });
+        }
+    }
+
+    // Called when nothing parses at the current token. Follows the call up to its matching `)`
+    // to tell why it failed
+    fn diagnose(&mut self) {
+        let keyword = match self.peek().token_type {
+            TokenType::Mul | TokenType::Do | TokenType::Dont => true,
+            TokenType::Add | TokenType::Sub | TokenType::Div | TokenType::Set => self.extended,
+            _ => false,
+        };
+        if self.diagnostics.is_none()
+            || !keyword
+            || self.maybe_peek_at(1).map(|token| token.token_type) != Some(TokenType::LeftParen)
+        {
+            return;
+        }
+
+        let mut depth = 0;
+        let mut length = 0;
+        let mut closed = false;
+
+        while let Some(token) = self.maybe_peek_at(length) {
+            if token.token_type == TokenType::Other {
+                break;
+            }
+            length += 1;
+
+            match token.token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 && token.token_type == TokenType::RightParen {
+                closed = true;
+                break;
+            }
+        }
+
+        let (first, last) = (self.peek(), self.peek_at(length - 1));
+        if !closed {
+            self.report(DiagnosticKind::Unterminated, first, last);
+            return;
+        }
+
+        // However many gaps there are, the call is rejected once for them
+        let mut reported = (1..length).any(|offset| !self.adjacent(offset));
+        if reported {
+            self.report(DiagnosticKind::Whitespace, first, last);
+        }
+
+        for offset in 0..length {
+            let token = self.peek_at(offset);
+
+            if token.token_type == TokenType::NumericLiteral && token.length > MAX_DIGITS {
+                let kind = DiagnosticKind::NumberTooLong {
+                    digits: token.length,
+                };
+                self.report(kind, token, token);
+                reported = true;
+            }
+        }
+
+        if !reported {
+            self.report(DiagnosticKind::Malformed, first, last);
+        }
+    }
+
     fn next_expr(&mut self) -> Option<Expr> {
         self.advance_while(|c| c.token_type == TokenType::Other);
         if self.eof() {
@@ -135,10 +396,21 @@ impl Compiler<'_> {
             return Some(dont);
         }
 
+        if self.extended {
+            if let Some(expr) = self.parse_extended() {
+                return Some(expr);
+            }
+
+            self.diagnose();
+            self.advance(1);
+            return self.next_expr();
+        }
+
         if let Some(mul) = self.parse_mul() {
             return Some(Expr::Mul(mul.0, mul.1));
         }
 
+        self.diagnose();
         self.advance(1);
         self.next_expr()
     }
@@ -168,10 +440,7 @@ mod tests {
 
         println!("{:?}", tokens);
 
-        let mut compiler = Compiler {
-            tokens: &tokens,
-            source: input,
-        };
+        let mut compiler = Compiler::new(&tokens, input);
 
         let result = compiler.parse_mul();
 
@@ -187,10 +456,7 @@ mod tests {
 
         let tokens = tokenizer.collect::<Vec<Token>>();
 
-        let mut compiler = Compiler {
-            tokens: &tokens,
-            source: input,
-        };
+        let mut compiler = Compiler::new(&tokens, input);
 
         let result = compiler.next_expr();
         println!("{:?}", result);
@@ -204,4 +470,61 @@ mod tests {
 
         assert_eq!(result, Some(Expr::Dont));
     }
+
+    #[test]
+    fn test_extended() {
+        let input = "mul(mul(2,3),4)xset($a,div(9,2))&add($a,sub(1,mul(2,$b))mul(1,2";
+        let chars = input.chars().collect::<Vec<char>>();
+
+        let tokens = super::super::tokenizer::MulTokenizer::new(&chars).collect::<Vec<Token>>();
+
+        // The original language only sees the inner call
+        let exprs = Compiler::new(&tokens, input).collect::<Vec<_>>();
+        assert_eq!(exprs, vec![Expr::Mul(2, 3)]);
+
+        let exprs = Compiler::new(&tokens, input)
+            .extended()
+            .map(|expr| expr.to_string())
+            .collect::<Vec<_>>();
+
+        // The unterminated add is skipped and its arguments are read on their own
+        assert_eq!(
+            exprs,
+            vec!["mul(mul(2,3),4)", "set($a,div(9,2))", "sub(1,mul(2,$b))",]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let input = "mul ( 2,3)mul(1234,5)&mul(1,2,3)mul[3,7]do(1)mul(4,5)mul(12,";
+        let chars = input.chars().collect::<Vec<char>>();
+
+        let tokens = super::super::tokenizer::MulTokenizer::new(&chars).collect::<Vec<Token>>();
+
+        let mut compiler = Compiler::new(&tokens, input).with_diagnostics();
+        let exprs = compiler.by_ref().collect::<Vec<_>>();
+        assert_eq!(exprs, vec![Expr::Mul(4, 5)]);
+
+        let diagnostics = compiler
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.kind, &input[diagnostic.span.clone()]))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                (DiagnosticKind::Whitespace, "mul ( 2,3)"),
+                (DiagnosticKind::NumberTooLong { digits: 4 }, "1234"),
+                (DiagnosticKind::Malformed, "mul(1,2,3)"),
+                (DiagnosticKind::Malformed, "do(1)"),
+                (DiagnosticKind::Unterminated, "mul(12,"),
+            ]
+        );
+
+        // Nothing is recorded unless asked for
+        let mut compiler = Compiler::new(&tokens, input);
+        compiler.by_ref().for_each(drop);
+        assert!(compiler.diagnostics().is_empty());
+    }
 }
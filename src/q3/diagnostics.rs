@@ -0,0 +1,125 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+use super::compiler::MAX_DIGITS;
+
+// Characters of the source line shown on each side of a span
+const CONTEXT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    // The source ends, or something other than a number, comma or paren comes, before the `)`
+    Unterminated,
+    // Spans the whole call, however many gaps it has
+    Whitespace,
+    NumberTooLong { digits: usize },
+    // Closed, but the arguments do not fit the call, as in `mul(1,2,3)`
+    Malformed,
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unterminated => write!(f, "call is never closed"),
+            Self::Whitespace => write!(f, "whitespace inside a call"),
+            Self::NumberTooLong { digits } => {
+                write!(
+                    f,
+                    "number has {} digits, at most {} are allowed",
+                    digits, MAX_DIGITS
+                )
+            }
+            Self::Malformed => write!(f, "arguments do not fit the call"),
+        }
+    }
+}
+
+// A call the compiler rejected, `span` is in bytes of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub span: Range<usize>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}: {}", self.span.start, self.span.end, self.kind)
+    }
+}
+
+impl Diagnostic {
+    // The position and message, then the part of the line around the span with carets under it
+    pub fn render(&self, source: &str) -> String {
+        let Range { start, end } = self.span;
+
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line = source[..start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count() + 1;
+
+        let underline_end = end.min(line_end).max(start);
+        let before = source[line_start..start]
+            .chars()
+            .rev()
+            .take(CONTEXT)
+            .map(char::len_utf8)
+            .sum::<usize>();
+        let after = source[underline_end..line_end]
+            .chars()
+            .take(CONTEXT)
+            .map(char::len_utf8)
+            .sum::<usize>();
+        let (snippet_start, snippet_end) = (start - before, underline_end + after);
+
+        let padding = source[snippet_start..start].chars().count();
+        let carets = source[start..underline_end].chars().count().max(1);
+
+        format!(
+            "{}:{}: {}\n{}\n{}{}",
+            line,
+            column,
+            self.kind,
+            &source[snippet_start..snippet_end],
+            " ".repeat(padding),
+            "^".repeat(carets)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let source = "first line\nxmul(12,%&mul(2,3)";
+        let diagnostic = Diagnostic {
+            kind: DiagnosticKind::Unterminated,
+            span: 12..19,
+        };
+
+        assert_eq!(diagnostic.to_string(), "12..19: call is never closed");
+        assert_eq!(
+            diagnostic.render(source),
+            "2:2: call is never closed\nxmul(12,%&mul(2,3)\n ^^^^^^^"
+        );
+
+        // Only some context of a long line is shown
+        let source = format!("{}mul(1234,5){}", "a".repeat(50), "b".repeat(50));
+        let diagnostic = Diagnostic {
+            kind: DiagnosticKind::NumberTooLong { digits: 4 },
+            span: 54..58,
+        };
+        assert_eq!(
+            diagnostic.render(&source),
+            format!(
+                "1:55: number has 4 digits, at most 3 are allowed\n{}mul(1234,5){}\n{}^^^^",
+                "a".repeat(16),
+                "b".repeat(17),
+                " ".repeat(20)
+            )
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use hashbrown::HashMap;
+
+use super::compiler::{BinaryOp, Expr, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterpretError {
+    DivisionByZero,
+    Overflow,
+    UndefinedRegister { name: String },
+}
+
+impl Display for InterpretError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::Overflow => write!(f, "arithmetic overflow"),
+            Self::UndefinedRegister { name } => write!(f, "register ${} is never set", name),
+        }
+    }
+}
+
+impl std::error::Error for InterpretError {}
+
+// Runs the recovered program. While enabled, every call adds its value to the total and `set`
+// stores a register, `don't()` skips both until the next `do()`
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    registers: HashMap<String, i64>,
+    enabled: bool,
+    total: i64,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            registers: HashMap::new(),
+            enabled: true,
+            total: 0,
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn register(&self, name: &str) -> Option<i64> {
+        self.registers.get(name).copied()
+    }
+
+    fn apply(op: BinaryOp, a: i64, b: i64) -> Result<i64, InterpretError> {
+        let result = match op {
+            BinaryOp::Mul => a.checked_mul(b),
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Sub => a.checked_sub(b),
+            BinaryOp::Div if b == 0 => return Err(InterpretError::DivisionByZero),
+            BinaryOp::Div => a.checked_div(b),
+        };
+
+        result.ok_or(InterpretError::Overflow)
+    }
+
+    pub fn evaluate(&self, value: &Value) -> Result<i64, InterpretError> {
+        match value {
+            Value::Literal(n) => Ok(*n),
+            Value::Register(name) => self
+                .register(name)
+                .ok_or_else(|| InterpretError::UndefinedRegister { name: name.clone() }),
+            Value::Call(op, a, b) => Self::apply(*op, self.evaluate(a)?, self.evaluate(b)?),
+        }
+    }
+
+    pub fn execute(&mut self, expr: &Expr) -> Result<(), InterpretError> {
+        match expr {
+            Expr::Do => self.enabled = true,
+            Expr::Dont => self.enabled = false,
+            _ if !self.enabled => {}
+            Expr::Mul(a, b) => {
+                let value = Self::apply(BinaryOp::Mul, *a as i64, *b as i64)?;
+                self.total = Self::apply(BinaryOp::Add, self.total, value)?;
+            }
+            Expr::Call(op, a, b) => {
+                let value = Self::apply(*op, self.evaluate(a)?, self.evaluate(b)?)?;
+                self.total = Self::apply(BinaryOp::Add, self.total, value)?;
+            }
+            Expr::Set(name, value) => {
+                let value = self.evaluate(value)?;
+                self.registers.insert(name.clone(), value);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn run(mut self, program: impl IntoIterator<Item = Expr>) -> Result<i64, InterpretError> {
+        for expr in program {
+            self.execute(&expr)?;
+        }
+
+        Ok(self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compiler::Compiler;
+    use super::super::tokenizer::{MulTokenizer, Token};
+    use super::*;
+
+    fn run_extended(input: &str) -> Result<Interpreter, InterpretError> {
+        let chars = input.chars().collect::<Vec<char>>();
+        let tokens = MulTokenizer::new(&chars).collect::<Vec<Token>>();
+
+        let mut interpreter = Interpreter::new();
+        for expr in Compiler::new(&tokens, input).extended() {
+            interpreter.execute(&expr)?;
+        }
+
+        Ok(interpreter)
+    }
+
+    #[test]
+    fn test_run_extended() {
+        let input =
+            "set($a,add(2,3))?mul($a,sub(10,div(7,2)))don't()set($a,100)add($a,1)do()mul($a,2)";
+        let interpreter = run_extended(input).unwrap();
+
+        assert_eq!(interpreter.register("a"), Some(5));
+        assert_eq!(interpreter.total(), 5 * 7 + 10);
+        assert!(interpreter.enabled());
+    }
+
+    #[test]
+    fn test_run_errors() {
+        assert_eq!(
+            run_extended("add($x,1)").err(),
+            Some(InterpretError::UndefinedRegister {
+                name: "x".to_string()
+            })
+        );
+        assert_eq!(
+            run_extended("div(1,sub(2,2))").err(),
+            Some(InterpretError::DivisionByZero)
+        );
+
+        // Disabled statements are never evaluated
+        assert_eq!(run_extended("don't()div(1,0)").unwrap().total(), 0);
+    }
+}
@@ -1,7 +1,11 @@
 use compiler::Compiler;
+use diagnostics::Diagnostic;
+use interpreter::{InterpretError, Interpreter};
 use tokenizer::Token;
 
 pub mod compiler;
+pub mod diagnostics;
+pub mod interpreter;
 pub mod tokenizer;
 
 fn compute_multiplication(input: &str) -> Vec<i32> {
@@ -21,11 +25,14 @@ fn compute_multiplication(input: &str) -> Vec<i32> {
         .collect()
 }
 
-pub fn compute_multiplication_sum(input: &str) -> i32 {
-    compute_multiplication(input).iter().sum()
+pub fn compute_multiplication_sum(input: &str) -> Result<i32, InterpretError> {
+    compute_multiplication(input)
+        .iter()
+        .try_fold(0i32, |total, product| total.checked_add(*product))
+        .ok_or(InterpretError::Overflow)
 }
 
-pub fn compute_multiplication_sum_op_aware(input: &str) -> i32 {
+pub fn compute_multiplication_sum_op_aware(input: &str) -> Result<i32, InterpretError> {
     let chars = input.chars().collect::<Vec<char>>();
     let tokenizer = tokenizer::MulTokenizer::new(&chars);
 
@@ -33,27 +40,27 @@ pub fn compute_multiplication_sum_op_aware(input: &str) -> i32 {
 
     let compiler = Compiler::new(&tokens, input);
 
-    let mut mul_enabled = true;
-
-    let mut result = 0;
-
-    for expr in compiler {
-        match expr {
-            compiler::Expr::Mul(a, b) => {
-                if mul_enabled {
-                    result += a * b;
-                }
-            }
-            compiler::Expr::Do => {
-                mul_enabled = true;
-            }
-            compiler::Expr::Dont => {
-                mul_enabled = false;
-            }
-        }
-    }
+    let total = Interpreter::new().run(compiler)?;
+    i32::try_from(total).map_err(|_| InterpretError::Overflow)
+}
+
+// Runs the memory as a program of the extended language, see `Compiler::extended`
+pub fn evaluate_extended_program(input: &str) -> Result<i64, InterpretError> {
+    let chars = input.chars().collect::<Vec<char>>();
+    let tokens = tokenizer::MulTokenizer::new(&chars).collect::<Vec<Token>>();
 
-    result
+    Interpreter::new().run(Compiler::new(&tokens, input).extended())
+}
+
+// Every call the original language rejects, for auditing the parser
+pub fn diagnose(input: &str) -> Vec<Diagnostic> {
+    let chars = input.chars().collect::<Vec<char>>();
+    let tokens = tokenizer::MulTokenizer::new(&chars).collect::<Vec<Token>>();
+
+    let mut compiler = Compiler::new(&tokens, input).with_diagnostics();
+    compiler.by_ref().for_each(drop);
+
+    compiler.diagnostics().to_vec()
 }
 
 #[cfg(test)]
@@ -74,6 +81,81 @@ mod tests {
     fn test_compute_multiplication_sum_op_aware() {
         let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
 
-        assert_eq!(compute_multiplication_sum_op_aware(input), 48);
+        assert_eq!(compute_multiplication_sum_op_aware(input), Ok(48));
+    }
+
+    #[test]
+    fn test_evaluate_extended_program() {
+        let input =
+            "xmul(2,4)&set($x,mul(3,add(1,1)))!^don't()_mul(5,5)+do()(mul(div($x,4),8)undo()?";
+
+        assert_eq!(evaluate_extended_program(input), Ok(8 + 8));
+    }
+
+    #[test]
+    fn test_rejects_spaced_calls_and_long_numbers() {
+        // The tokenizer skips whitespace, but calls only count when written without it, and
+        // numbers have at most three digits
+        let input = "mul(2,3)mul ( 2,3)mul(2 ,3)mul(1234,5)do ()mul(4,5)don't ()mul(6,7)";
+
+        assert_eq!(compute_multiplication(input), vec![6, 20, 42]);
+        assert_eq!(compute_multiplication_sum_op_aware(input), Ok(6 + 20 + 42));
+        assert_eq!(
+            evaluate_extended_program("add(1, 2)sub(9,1000)div(8,2)"),
+            Ok(4)
+        );
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let input = "xmul(2,4)%&mul[3,7]\nmul ( 2,3)mul(32,64]é+mul(12,";
+
+        let rendered = diagnose(input)
+            .iter()
+            .map(|diagnostic| diagnostic.render(input))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rendered,
+            vec![
+                "2:1: whitespace inside a call\nmul ( 2,3)mul(32,64]é+mul(12,\n^^^^^^^^^^",
+                "2:11: call is never closed\nmul ( 2,3)mul(32,64]é+mul(12,\n          ^^^^^^^^^",
+                "2:23: call is never closed\nl ( 2,3)mul(32,64]é+mul(12,\n                    ^^^^^^^",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multi_byte_characters() {
+        // Token positions count bytes, `é` and `→` take more than one
+        assert_eq!(compute_multiplication("é+mul(12,34)"), vec![408]);
+        assert_eq!(evaluate_extended_program("→add(mul(2,3),1)é"), Ok(7));
+
+        let input = "é+mul(12,34)mul(1, 2)";
+        let diagnostics = diagnose(input);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, 13..22);
+        assert_eq!(
+            diagnostics[0].render(input),
+            "1:13: whitespace inside a call\né+mul(12,34)mul(1, 2)\n            ^^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn test_compute_multiplication_sum_overflow() {
+        let input = "mul(999,999)".repeat(2200);
+
+        assert_eq!(
+            compute_multiplication_sum(&input),
+            Err(InterpretError::Overflow)
+        );
+        assert_eq!(
+            compute_multiplication_sum_op_aware(&input),
+            Err(InterpretError::Overflow)
+        );
+        assert_eq!(
+            compute_multiplication_sum(&"mul(999,999)".repeat(2000)),
+            Ok(999 * 999 * 2000)
+        );
     }
 }
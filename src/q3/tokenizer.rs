@@ -10,8 +10,15 @@ pub enum TokenType {
     Other,
     Do,
     Dont,
+    Add,
+    Sub,
+    Div,
+    Set,
+    // `$` followed by letters, the name is everything after the `$`
+    Register,
 }
 
+// `pos` and `length` are in bytes of the source string, so a token can be sliced out of it
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Token {
     pub token_type: TokenType,
@@ -31,7 +38,7 @@ impl Default for Token {
 
 pub struct MulTokenizer<'a> {
     source: &'a [char],
-    removed_chars: usize,
+    removed_bytes: usize,
 }
 
 impl Tokenizer for MulTokenizer<'_> {
@@ -42,11 +49,25 @@ impl Tokenizer for MulTokenizer<'_> {
     }
 
     fn advance(&mut self, n: usize) {
-        self.removed_chars += n;
+        self.removed_bytes += self.byte_length(n);
         self.source = &self.source[n..];
     }
 }
 
+// `don` is only `don't` when followed by `'t`
+fn keyword(word: &[char]) -> Option<TokenType> {
+    match word {
+        ['m', 'u', 'l'] => Some(TokenType::Mul),
+        ['a', 'd', 'd'] => Some(TokenType::Add),
+        ['s', 'u', 'b'] => Some(TokenType::Sub),
+        ['d', 'i', 'v'] => Some(TokenType::Div),
+        ['s', 'e', 't'] => Some(TokenType::Set),
+        ['d', 'o'] => Some(TokenType::Do),
+        ['d', 'o', 'n'] => Some(TokenType::Dont),
+        _ => None,
+    }
+}
+
 impl MulTokenizer<'_> {
     fn single_char_token(&mut self) -> Option<Token> {
         let token = match self.peek() {
@@ -88,36 +109,54 @@ impl MulTokenizer<'_> {
             n += 1;
         }
 
-        match &self.source[..n] {
-            ['m', 'u', 'l'] => {
-                let token = Some(self.make_token(TokenType::Mul, n));
-                self.advance(n);
-                return token;
-            }
-            ['d', 'o'] => {
-                let token = Some(self.make_token(TokenType::Do, n));
-                self.advance(n);
-                return token;
-            }
-            ['d', 'o', 'n'] => {
-                if let (Some('\''), Some('t')) = (self.maybe_peek_at(n), self.maybe_peek_at(n + 1))
-                {
-                    let token = Some(self.make_token(TokenType::Dont, n + 2));
-                    self.advance(n + 2);
-                    return token;
-                }
-            }
-            _ => {}
+        let (token_type, length) = match keyword(&self.source[..n])? {
+            TokenType::Dont => match (self.maybe_peek_at(n), self.maybe_peek_at(n + 1)) {
+                (Some('\''), Some('t')) => (TokenType::Dont, n + 2),
+                _ => return None,
+            },
+            token_type => (token_type, n),
+        };
+
+        let token = Some(self.make_token(token_type, length));
+        self.advance(length);
+        token
+    }
+
+    fn register(&mut self) -> Option<Token> {
+        if self.peek() != '$' {
+            return None;
+        }
+
+        let mut n = 1;
+
+        while !self.eof_at(n) && self.peek_at(n).is_ascii_alphabetic() {
+            n += 1;
+        }
+
+        // Keywords are found wherever their letters end a word, as in `xmul`, so a name ending
+        // in one would hide it
+        let name = &self.source[1..n];
+        if name.is_empty() || (0..name.len()).any(|i| keyword(&name[i..]).is_some()) {
+            return None;
         }
 
-        None
+        let token = Some(self.make_token(TokenType::Register, n));
+
+        self.advance(n);
+
+        token
+    }
+
+    // The UTF-8 length of the next `n` chars
+    fn byte_length(&self, n: usize) -> usize {
+        self.source[..n].iter().map(|c| c.len_utf8()).sum()
     }
 
     fn make_token(&self, token_type: TokenType, length: usize) -> Token {
         Token {
             token_type,
-            pos: self.removed_chars,
-            length,
+            pos: self.removed_bytes,
+            length: self.byte_length(length),
         }
     }
 
@@ -140,6 +179,10 @@ impl MulTokenizer<'_> {
             return Some(token);
         }
 
+        if let Some(token) = self.register() {
+            return Some(token);
+        }
+
         let other = Some(self.make_token(TokenType::Other, 1));
         self.advance(1);
         other
@@ -147,10 +190,10 @@ impl MulTokenizer<'_> {
 }
 
 impl MulTokenizer<'_> {
-    pub fn new(source: &[char]) -> MulTokenizer {
+    pub fn new(source: &[char]) -> MulTokenizer<'_> {
         MulTokenizer {
             source,
-            removed_chars: 0,
+            removed_bytes: 0,
         }
     }
 }
@@ -260,4 +303,46 @@ mod tests {
 
         assert_eq!(filtered_tokens, expected_tokens);
     }
+
+    #[test]
+    fn test_tokenize_extended() {
+        let input = "set($acc,add(1,$b))subdivxadd$$mul";
+
+        let token_types: Vec<TokenType> = MulTokenizer::new(&input.chars().collect::<Vec<char>>())
+            .map(|t| t.token_type)
+            .collect();
+
+        let expected_token_types = vec![
+            TokenType::Set,
+            TokenType::LeftParen,
+            TokenType::Register,
+            TokenType::Comma,
+            TokenType::Add,
+            TokenType::LeftParen,
+            TokenType::NumericLiteral,
+            TokenType::Comma,
+            TokenType::Register,
+            TokenType::RightParen,
+            TokenType::RightParen,
+            // Keywords only count when they are a whole word, like `mul`
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Add,
+            TokenType::Other,
+            TokenType::Other,
+            TokenType::Mul,
+        ];
+
+        assert_eq!(token_types, expected_token_types);
+
+        let register = MulTokenizer::new(&input.chars().collect::<Vec<char>>())
+            .nth(2)
+            .unwrap();
+        assert_eq!((register.pos, register.length), (4, 4));
+    }
 }